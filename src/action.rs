@@ -1,7 +1,27 @@
+use std::path::PathBuf;
+
+use crate::source::git::remote::RemoteOp;
+use crate::source::git::GitRequest;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Action {
     Quit,
     NextTab,
     PrevTab,
     Refresh,
+    InspectMemory(u32),
+    FindPathHolders(PathBuf),
+    ScanDirectory { root: PathBuf, exclude: Vec<String> },
+    /// Switch to the Disk Usage tab and scan this mount point
+    AnalyzeMount(PathBuf),
+    DeletePath(PathBuf),
+    TrashPath(PathBuf),
+    Git(GitRequest),
+    /// Fetch, pull or push `repo`, then re-run `reload`
+    GitRemote {
+        repo: PathBuf,
+        op: RemoteOp,
+        reload: Vec<GitRequest>,
+    },
+    CancelGitRemote(PathBuf),
 }
@@ -1,39 +1,74 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
 use crossterm::event::{KeyCode, KeyModifiers};
 use tokio::sync::mpsc;
 
 use crate::action::Action;
-use crate::config::Config;
+use crate::config::{Config, GitConfig};
 use crate::event::{Event, EventHandler};
+use crate::panel::disk_usage_panel::DiskUsagePanel;
 use crate::panel::git_panel::GitPanel;
+use crate::panel::network_panel::NetworkPanel;
+use crate::panel::process_panel::ProcessPanel;
 use crate::panel::system_panel::SystemPanel;
 use crate::panel::Panel;
-use crate::source::DataSnapshot;
+use crate::source::du::{self, DuEvent};
+use crate::source::git;
+use crate::source::holders::{self, HoldersResult};
+use crate::source::smaps::{self, SmapsResult};
+use crate::source::{build_globset, DataSnapshot};
 use crate::tui::Tui;
 use crate::ui::layout::AppLayout;
 use crate::ui::statusbar::draw_statusbar;
 use crate::ui::tabs::draw_tabs;
 
-const TAB_TITLES: &[&str] = &["System", "Git"];
+const TAB_TITLES: &[&str] = &["System", "Disk Usage", "Processes", "Network", "Git"];
 
 pub struct App {
     running: bool,
     selected_tab: usize,
     system_panel: SystemPanel,
+    disk_usage_panel: DiskUsagePanel,
+    process_panel: ProcessPanel,
+    network_panel: NetworkPanel,
     git_panel: GitPanel,
+    data_tx: mpsc::UnboundedSender<DataSnapshot>,
     data_rx: mpsc::UnboundedReceiver<DataSnapshot>,
     events: EventHandler,
+    /// Cancels the running directory scan when a new one is started.
+    du_cancel: Arc<AtomicBool>,
+    /// Cancel flags of fetches, pulls and pushes, by repo.
+    git_remote_cancel: HashMap<PathBuf, Arc<AtomicBool>>,
+    /// Shared with git jobs, which count refreshed repos by its options.
+    git_config: Arc<GitConfig>,
 }
 
 impl App {
-    pub fn new(config: &Config, data_rx: mpsc::UnboundedReceiver<DataSnapshot>) -> Self {
+    pub fn new(
+        config: &Config,
+        data_tx: mpsc::UnboundedSender<DataSnapshot>,
+        data_rx: mpsc::UnboundedReceiver<DataSnapshot>,
+    ) -> Self {
         let tick_rate = std::time::Duration::from_millis(config.tick_rate_ms);
         Self {
             running: true,
             selected_tab: 0,
-            system_panel: SystemPanel::new(),
-            git_panel: GitPanel::new(),
+            system_panel: SystemPanel::new(std::time::Duration::from_secs(
+                config.disk.forecast_horizon_mins * 60,
+            )),
+            disk_usage_panel: DiskUsagePanel::new(&config.disk_usage),
+            process_panel: ProcessPanel::new(),
+            network_panel: NetworkPanel::new(),
+            git_panel: GitPanel::new(&config.git),
+            data_tx,
             data_rx,
             events: EventHandler::new(tick_rate),
+            du_cancel: Arc::new(AtomicBool::new(false)),
+            git_remote_cancel: HashMap::new(),
+            git_config: Arc::new(config.git.clone()),
         }
     }
 
@@ -46,11 +81,21 @@ impl App {
 
                 match self.selected_tab {
                     0 => self.system_panel.draw(f, layout.content),
-                    1 => self.git_panel.draw(f, layout.content),
+                    1 => self.disk_usage_panel.draw(f, layout.content),
+                    2 => self.process_panel.draw(f, layout.content),
+                    3 => self.network_panel.draw(f, layout.content),
+                    4 => self.git_panel.draw(f, layout.content),
                     _ => {}
                 }
 
-                draw_statusbar(f, layout.statusbar);
+                let warning = self.system_panel.disk_warning();
+                let notification = self.git_panel.notification();
+                draw_statusbar(
+                    f,
+                    layout.statusbar,
+                    warning.as_deref(),
+                    notification.as_deref(),
+                );
             })?;
 
             // Handle events
@@ -61,7 +106,14 @@ impl App {
                     }
                 }
                 Some(snapshot) = self.data_rx.recv() => {
+                    // The transfer is over, however it ended
+                    if let DataSnapshot::GitEvent(git::GitEvent::Remote { repo, .. }) = &snapshot {
+                        self.git_remote_cancel.remove(repo);
+                    }
                     self.system_panel.on_data(&snapshot);
+                    self.disk_usage_panel.on_data(&snapshot);
+                    self.process_panel.on_data(&snapshot);
+                    self.network_panel.on_data(&snapshot);
                     self.git_panel.on_data(&snapshot);
                 }
             }
@@ -69,17 +121,40 @@ impl App {
         Ok(())
     }
 
-    fn handle_event(&self, event: Event) -> Option<Action> {
+    fn active_panel(&mut self) -> &mut dyn Panel {
+        match self.selected_tab {
+            0 => &mut self.system_panel,
+            1 => &mut self.disk_usage_panel,
+            2 => &mut self.process_panel,
+            3 => &mut self.network_panel,
+            _ => &mut self.git_panel,
+        }
+    }
+
+    /// Run blocking work off the UI thread and feed its result back through
+    /// the data channel like any other snapshot.
+    fn spawn_job<F>(&self, job: F)
+    where
+        F: FnOnce() -> DataSnapshot + Send + 'static,
+    {
+        let tx = self.data_tx.clone();
+        tokio::task::spawn_blocking(move || {
+            let _ = tx.send(job());
+        });
+    }
+
+    fn handle_event(&mut self, event: Event) -> Option<Action> {
         match event {
             Event::Key(key) => match key.code {
-                KeyCode::Char('q') => Some(Action::Quit),
                 KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                     Some(Action::Quit)
                 }
+                _ if self.active_panel().captures_input() => self.active_panel().handle_key(key),
+                KeyCode::Char('q') => Some(Action::Quit),
                 KeyCode::Tab => Some(Action::NextTab),
                 KeyCode::BackTab => Some(Action::PrevTab),
                 KeyCode::Char('r') => Some(Action::Refresh),
-                _ => None,
+                _ => self.active_panel().handle_key(key),
             },
             Event::Tick => None,
             Event::Resize(_, _) => None,
@@ -100,7 +175,86 @@ impl App {
                 };
             }
             Action::Refresh => {
-                // Will trigger data refresh in later phases
+                if let Some(action) = self.active_panel().refresh() {
+                    self.dispatch(action);
+                }
+            }
+            Action::InspectMemory(pid) => {
+                self.spawn_job(move || {
+                    DataSnapshot::Smaps(SmapsResult {
+                        pid,
+                        report: smaps::read_smaps(pid).map_err(|e| e.to_string()),
+                    })
+                });
+            }
+            Action::FindPathHolders(query) => {
+                self.spawn_job(move || {
+                    let report = holders::find_holders(&query).map_err(|e| e.to_string());
+                    DataSnapshot::Holders(HoldersResult { query, report })
+                });
+            }
+            Action::ScanDirectory { root, exclude } => {
+                self.du_cancel.store(true, Ordering::Relaxed);
+                let cancel = Arc::new(AtomicBool::new(false));
+                self.du_cancel = cancel.clone();
+
+                let tx = self.data_tx.clone();
+                tokio::task::spawn_blocking(move || {
+                    let result = build_globset(&exclude).and_then(|excludes| {
+                        du::scan(&root, &excludes, &cancel, |progress| {
+                            let _ = tx.send(DataSnapshot::Du(DuEvent::Progress(progress)));
+                        })
+                        .map_err(|e| e.to_string())
+                    });
+                    let done = match result {
+                        Ok(Some(tree)) => Ok(Arc::new(tree)),
+                        // Superseded by a newer scan, which will report instead
+                        Ok(None) => return,
+                        Err(e) => Err(e),
+                    };
+                    let _ = tx.send(DataSnapshot::Du(DuEvent::Done(done)));
+                });
+            }
+            Action::AnalyzeMount(mount) => {
+                self.selected_tab = 1;
+                let action = self
+                    .disk_usage_panel
+                    .scan(mount.to_string_lossy().to_string());
+                self.dispatch(action);
+            }
+            Action::DeletePath(path) => {
+                self.spawn_job(move || {
+                    let result = du::delete(&path).map_err(|e| e.to_string());
+                    DataSnapshot::Du(DuEvent::Removed { path, result })
+                });
+            }
+            Action::TrashPath(path) => {
+                self.spawn_job(move || {
+                    let result = du::trash(&path).map_err(|e| e.to_string());
+                    DataSnapshot::Du(DuEvent::Removed { path, result })
+                });
+            }
+            Action::Git(request) => {
+                let config = self.git_config.clone();
+                self.spawn_job(move || DataSnapshot::GitEvent(request.run(&config)));
+            }
+            Action::GitRemote { repo, op, reload } => {
+                let cancel = Arc::new(AtomicBool::new(false));
+                self.git_remote_cancel.insert(repo.clone(), cancel.clone());
+
+                let config = self.git_config.clone();
+                let tx = self.data_tx.clone();
+                self.spawn_job(move || {
+                    let done = git::run_remote(&config, repo, op, reload, &cancel, |event| {
+                        let _ = tx.send(DataSnapshot::GitEvent(event));
+                    });
+                    DataSnapshot::GitEvent(done)
+                });
+            }
+            Action::CancelGitRemote(repo) => {
+                if let Some(cancel) = self.git_remote_cancel.remove(&repo) {
+                    cancel.store(true, Ordering::Relaxed);
+                }
             }
         }
     }
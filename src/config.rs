@@ -8,6 +8,12 @@ pub struct Config {
 
     #[serde(default)]
     pub git: GitConfig,
+
+    #[serde(default)]
+    pub disk: DiskConfig,
+
+    #[serde(default)]
+    pub disk_usage: DiskUsageConfig,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -15,8 +21,218 @@ pub struct GitConfig {
     #[serde(default = "default_git_interval")]
     pub interval_secs: u64,
 
+    /// Refresh a repo as soon as its files change instead of waiting for
+    /// the next poll.
+    #[serde(default = "default_true")]
+    pub watch: bool,
+
+    /// Watched repos are still fully re-read this often, in case an event
+    /// was missed.
+    #[serde(default = "default_watch_fallback")]
+    pub watch_fallback_secs: u64,
+
+    /// Most repos read at once.
+    #[serde(default = "default_status_workers")]
+    pub status_workers: usize,
+
+    /// A repo read taking longer than this is shown as still scanning.
+    #[serde(default = "default_status_timeout")]
+    pub status_timeout_secs: u64,
+
+    /// What repo status counts; a repo's own `status` table replaces it.
+    #[serde(default)]
+    pub status: StatusConfig,
+
+    #[serde(default, deserialize_with = "deserialize_repos")]
+    pub repos: Vec<RepoConfig>,
+
+    /// Directories searched for repositories in addition to `repos`.
+    #[serde(default, deserialize_with = "deserialize_scan_roots")]
+    pub scan_roots: Vec<String>,
+
+    #[serde(default = "default_scan_max_depth")]
+    pub scan_max_depth: usize,
+
+    /// Glob patterns (matched against full paths) skipped while scanning.
+    #[serde(default)]
+    pub scan_ignore: Vec<String>,
+
+    #[serde(default)]
+    pub scan_follow_symlinks: bool,
+
+    #[serde(default = "default_scan_interval")]
+    pub scan_interval_secs: u64,
+
+    /// Initial commit message for repos without their own template.
+    #[serde(default)]
+    pub commit_template: Option<String>,
+
+    /// Fetch repos in the background this often; 0 turns it off.
+    #[serde(default)]
+    pub auto_fetch_secs: u64,
+
+    /// Up to this much is added to each repo's interval so fetches spread out.
+    #[serde(default = "default_auto_fetch_jitter")]
+    pub auto_fetch_jitter_secs: u64,
+
+    /// Most background fetches running at once.
+    #[serde(default = "default_auto_fetch_concurrency")]
+    pub auto_fetch_concurrency: usize,
+
+    /// Extra columns in the repo table, e.g. `["age", "subject", "changed"]`.
+    #[serde(default)]
+    pub columns: Vec<GitColumn>,
+
+    /// Initial order of the repo table.
+    #[serde(default)]
+    pub sort: GitSort,
+
+    /// Branches without commits for this many days count as stale; 0 only
+    /// reports merged branches and ones whose upstream is gone.
+    #[serde(default = "default_stale_days")]
+    pub stale_days: u64,
+}
+
+/// Optional columns of the repo table.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GitColumn {
+    /// Short SHA of HEAD
+    Commit,
+    /// Author of HEAD
+    Author,
+    /// How long ago HEAD was committed
+    Age,
+    /// Subject of HEAD
+    Subject,
+    /// How long ago anything changed locally
+    Changed,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum GitSort {
+    /// As listed in `repos`, then as discovered
+    #[default]
+    Listed,
+    /// Most recently committed first
+    Age,
+    /// Most recently changed first
+    Changed,
+    Author,
+    Subject,
+}
+
+/// Per-repo settings. In `ktop.toml` a repo is either a bare path or a table
+/// such as `{ path = "~/src/app", commit_template = "feat: " }`.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct RepoConfig {
+    pub path: String,
+
+    /// Shown instead of the directory name.
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// Overrides `interval_secs` for this repo.
+    #[serde(default)]
+    pub interval_secs: Option<u64>,
+
+    #[serde(default)]
+    pub status: Option<StatusConfig>,
+
+    #[serde(default)]
+    pub commit_template: Option<String>,
+
+    /// Overrides `auto_fetch_secs` for this repo; 0 turns it off.
+    #[serde(default)]
+    pub auto_fetch_secs: Option<u64>,
+}
+
+/// Options passed to git2's `StatusOptions` when counting changes.
+#[derive(Debug, Deserialize, Clone)]
+pub struct StatusConfig {
+    #[serde(default = "default_true")]
+    pub include_untracked: bool,
+
+    /// List files inside untracked directories rather than just the directory.
+    #[serde(default = "default_true")]
+    pub recurse_untracked_dirs: bool,
+
+    #[serde(default)]
+    pub include_ignored: bool,
+
+    #[serde(default)]
+    pub ignore_submodules: bool,
+
+    /// Only count paths matching these pathspecs, e.g. `["services/api"]`.
+    #[serde(default)]
+    pub pathspecs: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RepoSpec {
+    Path(String),
+    Table(RepoConfig),
+}
+
+fn deserialize_repos<'de, D>(deserializer: D) -> Result<Vec<RepoConfig>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let specs = Vec::<RepoSpec>::deserialize(deserializer)?;
+    Ok(specs
+        .into_iter()
+        .map(|spec| match spec {
+            RepoSpec::Path(path) => RepoConfig {
+                path,
+                ..RepoConfig::default()
+            },
+            RepoSpec::Table(repo) => repo,
+        })
+        .map(|repo| RepoConfig {
+            path: expand_home(&repo.path),
+            ..repo
+        })
+        .collect())
+}
+
+fn deserialize_scan_roots<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let roots = Vec::<String>::deserialize(deserializer)?;
+    Ok(roots.iter().map(|root| expand_home(root)).collect())
+}
+
+/// `~` or a leading `~/` replaced by `$HOME`.
+fn expand_home(path: &str) -> String {
+    let Ok(home) = std::env::var("HOME") else {
+        return path.to_string();
+    };
+    match path.strip_prefix('~') {
+        Some("") => home,
+        Some(rest) if rest.starts_with('/') => format!("{home}{rest}"),
+        _ => path.to_string(),
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct DiskConfig {
+    #[serde(default = "default_forecast_window")]
+    pub forecast_window_mins: u64,
+
+    #[serde(default = "default_forecast_horizon")]
+    pub forecast_horizon_mins: u64,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct DiskUsageConfig {
+    #[serde(default = "default_disk_usage_root")]
+    pub root: String,
+
     #[serde(default)]
-    pub repos: Vec<String>,
+    pub exclude: Vec<String>,
 }
 
 fn default_tick_rate() -> u64 {
@@ -27,11 +243,61 @@ fn default_git_interval() -> u64 {
     5
 }
 
+fn default_true() -> bool {
+    true
+}
+
+fn default_watch_fallback() -> u64 {
+    60
+}
+
+fn default_status_workers() -> usize {
+    4
+}
+
+fn default_status_timeout() -> u64 {
+    10
+}
+
+fn default_scan_max_depth() -> usize {
+    4
+}
+
+fn default_scan_interval() -> u64 {
+    60
+}
+
+fn default_auto_fetch_jitter() -> u64 {
+    60
+}
+
+fn default_auto_fetch_concurrency() -> usize {
+    2
+}
+
+fn default_stale_days() -> u64 {
+    90
+}
+
+fn default_forecast_window() -> u64 {
+    15
+}
+
+fn default_forecast_horizon() -> u64 {
+    120
+}
+
+fn default_disk_usage_root() -> String {
+    std::env::var("HOME").unwrap_or_else(|_| "/".to_string())
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             tick_rate_ms: default_tick_rate(),
             git: GitConfig::default(),
+            disk: DiskConfig::default(),
+            disk_usage: DiskUsageConfig::default(),
         }
     }
 }
@@ -40,11 +306,99 @@ impl Default for GitConfig {
     fn default() -> Self {
         Self {
             interval_secs: default_git_interval(),
+            watch: true,
+            watch_fallback_secs: default_watch_fallback(),
+            status_workers: default_status_workers(),
+            status_timeout_secs: default_status_timeout(),
+            status: StatusConfig::default(),
             repos: vec![],
+            scan_roots: vec![],
+            scan_max_depth: default_scan_max_depth(),
+            scan_ignore: vec![],
+            scan_follow_symlinks: false,
+            scan_interval_secs: default_scan_interval(),
+            commit_template: None,
+            auto_fetch_secs: 0,
+            auto_fetch_jitter_secs: default_auto_fetch_jitter(),
+            auto_fetch_concurrency: default_auto_fetch_concurrency(),
+            columns: vec![],
+            sort: GitSort::default(),
+            stale_days: default_stale_days(),
         }
     }
 }
 
+impl Default for StatusConfig {
+    fn default() -> Self {
+        Self {
+            include_untracked: true,
+            recurse_untracked_dirs: true,
+            include_ignored: false,
+            ignore_submodules: false,
+            pathspecs: vec![],
+        }
+    }
+}
+
+impl Default for DiskConfig {
+    fn default() -> Self {
+        Self {
+            forecast_window_mins: default_forecast_window(),
+            forecast_horizon_mins: default_forecast_horizon(),
+        }
+    }
+}
+
+impl Default for DiskUsageConfig {
+    fn default() -> Self {
+        Self {
+            root: default_disk_usage_root(),
+            exclude: vec![],
+        }
+    }
+}
+
+impl GitConfig {
+    /// Settings of the repo at `path`, if it is listed in `repos`.
+    fn repo(&self, path: &Path) -> Option<&RepoConfig> {
+        self.repos.iter().find(|r| Path::new(&r.path) == path)
+    }
+
+    /// Commit template for the repo at `path`, falling back to the global one.
+    pub fn commit_template(&self, path: &Path) -> Option<&str> {
+        self.repo(path)
+            .and_then(|r| r.commit_template.as_deref())
+            .or(self.commit_template.as_deref())
+    }
+
+    /// Background fetch interval for the repo at `path`, if it has one.
+    pub fn auto_fetch_secs(&self, path: &Path) -> Option<u64> {
+        let secs = self
+            .repo(path)
+            .and_then(|r| r.auto_fetch_secs)
+            .unwrap_or(self.auto_fetch_secs);
+        (secs > 0).then_some(secs)
+    }
+
+    pub fn display_name(&self, path: &Path) -> Option<&str> {
+        self.repo(path).and_then(|r| r.name.as_deref())
+    }
+
+    /// How often the repo at `path` is polled.
+    pub fn interval_secs(&self, path: &Path) -> u64 {
+        self.repo(path)
+            .and_then(|r| r.interval_secs)
+            .unwrap_or(self.interval_secs)
+    }
+
+    /// Status options for the repo at `path`, falling back to the global ones.
+    pub fn status(&self, path: &Path) -> &StatusConfig {
+        self.repo(path)
+            .and_then(|r| r.status.as_ref())
+            .unwrap_or(&self.status)
+    }
+}
+
 impl Config {
     pub fn load(path: &Path) -> crate::errors::Result<Self> {
         if path.exists() {
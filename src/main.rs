@@ -12,6 +12,7 @@ use std::path::PathBuf;
 
 use config::Config;
 use source::spawn_sources;
+use tokio::sync::mpsc;
 
 #[tokio::main]
 async fn main() -> color_eyre::Result<()> {
@@ -21,9 +22,10 @@ async fn main() -> color_eyre::Result<()> {
     let config_path = PathBuf::from("ktop.toml");
     let config = Config::load(&config_path).unwrap_or_default();
 
-    let data_rx = spawn_sources(&config);
+    let (data_tx, data_rx) = mpsc::unbounded_channel();
+    spawn_sources(&config, data_tx.clone());
     let mut terminal = tui::init()?;
-    let mut app = app::App::new(&config, data_rx);
+    let mut app = app::App::new(&config, data_tx, data_rx);
 
     let result = app.run(&mut terminal).await;
 
@@ -0,0 +1,351 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+use crate::action::Action;
+use crate::config::DiskUsageConfig;
+use crate::source::du::{DuEvent, DuProgress, DuTree};
+use crate::source::DataSnapshot;
+use crate::ui::format::format_bytes;
+use crate::ui::input::TextInput;
+use crate::ui::layout::centered_rect;
+
+use super::Panel;
+
+const BAR_WIDTH: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Removal {
+    Delete,
+    Trash,
+}
+
+pub struct DiskUsagePanel {
+    config: DiskUsageConfig,
+    tree: Option<Arc<DuTree>>,
+    /// Node currently being browsed.
+    cwd: usize,
+    /// Position in `cwd`'s (size-sorted) children.
+    selected: usize,
+    progress: Option<DuProgress>,
+    message: Option<String>,
+    root_prompt: Option<TextInput>,
+    confirm: Option<(PathBuf, Removal)>,
+}
+
+impl DiskUsagePanel {
+    pub fn new(config: &DiskUsageConfig) -> Self {
+        Self {
+            config: config.clone(),
+            tree: None,
+            cwd: 0,
+            selected: 0,
+            progress: None,
+            message: None,
+            root_prompt: None,
+            confirm: None,
+        }
+    }
+
+    /// Make `root` the scan root and start scanning it.
+    pub fn scan(&mut self, root: String) -> Action {
+        self.config.root = root.clone();
+        Action::ScanDirectory {
+            root: root.into(),
+            exclude: self.config.exclude.clone(),
+        }
+    }
+
+    fn children(&self) -> &[usize] {
+        match &self.tree {
+            Some(tree) => &tree.nodes[self.cwd].children,
+            None => &[],
+        }
+    }
+
+    fn selected_node(&self) -> Option<usize> {
+        self.children().get(self.selected).copied()
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let len = self.children().len();
+        if len == 0 {
+            self.selected = 0;
+            return;
+        }
+        self.selected = self.selected.saturating_add_signed(delta).min(len - 1);
+    }
+
+    fn enter(&mut self) {
+        let (Some(tree), Some(node)) = (&self.tree, self.selected_node()) else {
+            return;
+        };
+        if tree.nodes[node].is_dir {
+            self.cwd = node;
+            self.selected = 0;
+        }
+    }
+
+    fn leave(&mut self) {
+        let Some(tree) = &self.tree else { return };
+        if let Some(parent) = tree.nodes[self.cwd].parent {
+            let previous = self.cwd;
+            self.cwd = parent;
+            self.selected = tree.nodes[parent]
+                .children
+                .iter()
+                .position(|&c| c == previous)
+                .unwrap_or(0);
+        }
+    }
+
+    fn on_removed(&mut self, path: &Path, result: &Result<(), String>) {
+        match result {
+            Ok(()) => {
+                if let Some(tree) = &mut self.tree {
+                    if let Some(index) = tree.find(path) {
+                        Arc::make_mut(tree).remove(index);
+                    }
+                }
+                self.message = Some(format!("Removed {}", path.display()));
+                self.move_selection(0);
+            }
+            Err(e) => self.message = Some(format!("Failed to remove {}: {e}", path.display())),
+        }
+    }
+
+    fn draw_listing(&self, f: &mut Frame, area: Rect) {
+        let Some(tree) = &self.tree else {
+            let text = match &self.progress {
+                Some(p) => format!(
+                    "Scanning {}… {} entries, {}",
+                    p.root.display(),
+                    p.entries,
+                    format_bytes(p.bytes)
+                ),
+                None => format!("Press s to scan {}", self.config.root),
+            };
+            let hint = Paragraph::new(text)
+                .block(Block::default().borders(Borders::ALL).title(" Disk Usage "));
+            f.render_widget(hint, area);
+            return;
+        };
+
+        let dir = &tree.nodes[self.cwd];
+        let rows: Vec<Row> = dir
+            .children
+            .iter()
+            .map(|&c| {
+                let node = &tree.nodes[c];
+                let ratio = if dir.size == 0 {
+                    0.0
+                } else {
+                    node.size as f64 / dir.size as f64
+                };
+                let filled = (ratio * BAR_WIDTH as f64).round() as usize;
+                let bar = format!(
+                    "{}{}",
+                    "█".repeat(filled),
+                    "░".repeat(BAR_WIDTH.saturating_sub(filled))
+                );
+                let name = if node.is_dir {
+                    Cell::from(format!("{}/", node.name)).style(
+                        Style::default()
+                            .fg(Color::Cyan)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                } else {
+                    Cell::from(node.name.clone())
+                };
+                Row::new(vec![
+                    Cell::from(format_bytes(node.size)),
+                    Cell::from(bar).style(Style::default().fg(Color::Yellow)),
+                    Cell::from(format!("{:5.1}%", ratio * 100.0)),
+                    name,
+                ])
+            })
+            .collect();
+
+        let widths = [
+            Constraint::Length(10),
+            Constraint::Length(BAR_WIDTH as u16),
+            Constraint::Length(7),
+            Constraint::Min(20),
+        ];
+
+        let mut title = format!(
+            " {} — {} ",
+            tree.path(self.cwd).display(),
+            format_bytes(dir.size)
+        );
+        if tree.errors > 0 {
+            title.push_str(&format!("({} unreadable) ", tree.errors));
+        }
+        if self.progress.is_some() {
+            title.push_str("(rescanning…) ");
+        }
+
+        let table = Table::new(rows, widths)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .style(Style::default().fg(Color::White))
+            .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        let mut state = TableState::default().with_selected(Some(self.selected));
+        f.render_stateful_widget(table, area, &mut state);
+    }
+
+    fn draw_confirm(&self, f: &mut Frame, area: Rect, path: &Path, removal: Removal) {
+        let size = self
+            .tree
+            .as_ref()
+            .and_then(|t| t.find(path).map(|i| t.nodes[i].size))
+            .unwrap_or(0);
+        let verb = match removal {
+            Removal::Delete => "Permanently delete",
+            Removal::Trash => "Move to trash",
+        };
+
+        let popup = centered_rect(area, 60, 5);
+        f.render_widget(Clear, popup);
+        let text = vec![
+            Line::from(format!(
+                "{verb} {} ({})?",
+                path.display(),
+                format_bytes(size)
+            )),
+            Line::from(vec![
+                Span::styled("y", Style::default().fg(Color::Yellow)),
+                Span::raw(": confirm  "),
+                Span::styled("any other key", Style::default().fg(Color::Yellow)),
+                Span::raw(": cancel"),
+            ]),
+        ];
+        let dialog = Paragraph::new(text).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Red))
+                .title(" Confirm "),
+        );
+        f.render_widget(dialog, popup);
+    }
+}
+
+impl Panel for DiskUsagePanel {
+    fn on_data(&mut self, data: &DataSnapshot) {
+        let DataSnapshot::Du(event) = data else {
+            return;
+        };
+        match event {
+            DuEvent::Progress(progress) => self.progress = Some(progress.clone()),
+            DuEvent::Done(result) => {
+                self.progress = None;
+                match result {
+                    Ok(tree) => {
+                        self.tree = Some(tree.clone());
+                        self.cwd = 0;
+                        self.selected = 0;
+                        self.message = None;
+                    }
+                    Err(e) => self.message = Some(format!("Scan failed: {e}")),
+                }
+            }
+            DuEvent::Removed { path, result } => self.on_removed(path, result),
+        }
+    }
+
+    fn draw(&self, f: &mut Frame, area: Rect) {
+        let mut constraints = vec![Constraint::Min(3)];
+        if self.message.is_some() {
+            constraints.push(Constraint::Length(1));
+        }
+        if self.root_prompt.is_some() {
+            constraints.push(Constraint::Length(3));
+        }
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(area);
+
+        self.draw_listing(f, chunks[0]);
+
+        let mut next = 1;
+        if let Some(message) = &self.message {
+            f.render_widget(
+                Paragraph::new(Span::styled(
+                    message.as_str(),
+                    Style::default().fg(Color::Yellow),
+                )),
+                chunks[next],
+            );
+            next += 1;
+        }
+        if let Some(input) = &self.root_prompt {
+            input.draw(f, chunks[next], "Scan directory (Enter: scan, Esc: cancel)");
+        }
+
+        if let Some((path, removal)) = &self.confirm {
+            self.draw_confirm(f, area, path, *removal);
+        }
+    }
+
+    fn captures_input(&self) -> bool {
+        self.root_prompt.is_some() || self.confirm.is_some()
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Option<Action> {
+        if let Some((path, removal)) = self.confirm.take() {
+            if key.code != KeyCode::Char('y') {
+                return None;
+            }
+            return Some(match removal {
+                Removal::Delete => Action::DeletePath(path),
+                Removal::Trash => Action::TrashPath(path),
+            });
+        }
+
+        if let Some(input) = &mut self.root_prompt {
+            match key.code {
+                KeyCode::Esc => self.root_prompt = None,
+                KeyCode::Enter => {
+                    let root = input.value().trim().to_string();
+                    self.root_prompt = None;
+                    if !root.is_empty() {
+                        return Some(self.scan(root));
+                    }
+                }
+                _ => {
+                    input.handle_key(key);
+                }
+            }
+            return None;
+        }
+
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => self.enter(),
+            KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') => self.leave(),
+            KeyCode::Char('s') => self.root_prompt = Some(TextInput::new(&self.config.root)),
+            KeyCode::Char('d') | KeyCode::Char('t') => {
+                if let (Some(tree), Some(node)) = (&self.tree, self.selected_node()) {
+                    let removal = if key.code == KeyCode::Char('d') {
+                        Removal::Delete
+                    } else {
+                        Removal::Trash
+                    };
+                    self.confirm = Some((tree.path(node), removal));
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+}
@@ -1,95 +1,1275 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
-    layout::Rect,
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    widgets::{Block, Borders, Cell, Row, Table},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState},
     Frame,
 };
 
-use crate::source::git::GitSnapshot;
+use crate::action::Action;
+use crate::config::{GitColumn, GitConfig, GitSort};
+use crate::source::git::branches::BranchOp;
+use crate::source::git::index::IndexOp;
+use crate::source::git::remote::{RemoteOp, TransferProgress};
+use crate::source::git::stale::BranchRef;
+use crate::source::git::stash::StashOp;
+use crate::source::git::submodules::{SubmoduleInfo, SubmoduleOp};
+use crate::source::git::worktrees::{WorktreeInfo, WorktreeOp};
+use crate::source::git::{
+    GitEvent, GitRequest, GitSnapshot, LastCommit, RepoEntry, RepoError, RepoStatus, Upstream,
+};
 use crate::source::DataSnapshot;
+use crate::ui::format::{format_age, format_bytes};
+use crate::ui::layout::centered_rect;
 
 use super::Panel;
 
+mod branches;
+mod commit;
+mod diff;
+mod files;
+mod log;
+mod stale;
+mod stash;
+mod submodules;
+mod worktrees;
+
+use branches::BranchesView;
+use commit::{CommitComposer, ComposerKey};
+use diff::DiffView;
+use files::FilesView;
+use log::LogView;
+use stale::StaleView;
+use stash::StashView;
+use submodules::{checked_out, submodule_state, SubmodulesView};
+use worktrees::{worktree_branch, worktree_state, WorktreesView};
+
+/// Drill-down views stacked on top of the repository table.
+enum View {
+    Files(FilesView),
+    Diff(DiffView),
+    Log(Box<LogView>),
+    Branches(BranchesView),
+    Stash(StashView),
+    Worktrees(WorktreesView),
+    Submodules(SubmodulesView),
+    Stale(StaleView),
+}
+
+/// What a drill-down view wants the panel to do after a key press.
+enum Nav {
+    Stay,
+    Back,
+    /// Push a view on top and load its data
+    Open(View),
+    Run(Action),
+    /// Change the index or worktree of `repo`
+    Apply {
+        repo: PathBuf,
+        op: IndexOp,
+    },
+    /// Check out, create, rename or delete a branch of `repo`
+    Branch {
+        repo: PathBuf,
+        op: BranchOp,
+    },
+    /// Apply, pop or drop a stash of `repo`
+    Stash {
+        repo: PathBuf,
+        op: StashOp,
+    },
+    /// Add or prune a linked worktree of `repo`
+    Worktree {
+        repo: PathBuf,
+        op: WorktreeOp,
+    },
+    /// Init and update submodules of `repo`
+    Submodule {
+        repo: PathBuf,
+        op: SubmoduleOp,
+    },
+    /// Delete stale branches across repos
+    DeleteBranches(Vec<BranchRef>),
+}
+
+impl View {
+    /// The request that (re)loads this view's data.
+    fn request(&self) -> GitRequest {
+        match self {
+            View::Files(v) => v.request(),
+            View::Diff(v) => v.request(),
+            View::Log(v) => v.request(),
+            View::Branches(v) => v.request(),
+            View::Stash(v) => v.request(),
+            View::Worktrees(v) => v.request(),
+            View::Submodules(v) => v.request(),
+            View::Stale(v) => v.request(),
+        }
+    }
+
+    fn on_event(&mut self, event: &GitEvent) {
+        match self {
+            View::Files(v) => v.on_event(event),
+            View::Diff(v) => v.on_event(event),
+            View::Log(v) => v.on_event(event),
+            View::Branches(v) => v.on_event(event),
+            View::Stash(v) => v.on_event(event),
+            View::Worktrees(v) => v.on_event(event),
+            View::Submodules(v) => v.on_event(event),
+            View::Stale(v) => v.on_event(event),
+        }
+    }
+
+    fn draw(&self, f: &mut Frame, area: Rect) {
+        match self {
+            View::Files(v) => v.draw(f, area),
+            View::Diff(v) => v.draw(f, area),
+            View::Log(v) => v.draw(f, area),
+            View::Branches(v) => v.draw(f, area),
+            View::Stash(v) => v.draw(f, area),
+            View::Worktrees(v) => v.draw(f, area),
+            View::Submodules(v) => v.draw(f, area),
+            View::Stale(v) => v.draw(f, area),
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Nav {
+        match self {
+            View::Files(v) => v.handle_key(key),
+            View::Diff(v) => v.handle_key(key),
+            View::Log(v) => v.handle_key(key),
+            View::Branches(v) => v.handle_key(key),
+            View::Stash(v) => v.handle_key(key),
+            View::Worktrees(v) => v.handle_key(key),
+            View::Submodules(v) => v.handle_key(key),
+            View::Stale(v) => v.handle_key(key),
+        }
+    }
+
+    /// The repo the view is about; `None` for cross-repo views.
+    fn repo(&self) -> Option<&Path> {
+        match self {
+            View::Files(v) => Some(v.repo()),
+            View::Diff(v) => Some(v.repo()),
+            View::Log(v) => Some(v.repo()),
+            View::Branches(v) => Some(v.repo()),
+            View::Stash(v) => Some(v.repo()),
+            View::Worktrees(v) => Some(v.repo()),
+            View::Submodules(v) => Some(v.repo()),
+            View::Stale(_) => None,
+        }
+    }
+
+    /// The view is editing text and wants every key.
+    fn captures_input(&self) -> bool {
+        match self {
+            View::Log(v) => v.captures_input(),
+            View::Branches(v) => v.captures_input(),
+            View::Stash(v) => v.captures_input(),
+            View::Worktrees(v) => v.captures_input(),
+            View::Stale(v) => v.captures_input(),
+            View::Files(_) | View::Diff(_) | View::Submodules(_) => false,
+        }
+    }
+}
+
+/// A fetch, pull or push in flight, shown under the panel.
+struct Transfer {
+    repo: PathBuf,
+    name: String,
+    op: RemoteOp,
+    progress: Option<TransferProgress>,
+}
+
+/// One-line message under the panel.
+enum Notice {
+    Info(String),
+    Error(String),
+}
+
 pub struct GitPanel {
+    config: GitConfig,
     snapshot: GitSnapshot,
+    /// Position of each repo in the last snapshot, for `GitSort::Listed`
+    listed: HashMap<PathBuf, usize>,
+    sort: GitSort,
+    selected: usize,
+    show_detail: bool,
+    views: Vec<View>,
+    notice: Option<Notice>,
+    confirm: Option<(PathBuf, IndexOp)>,
+    composer: Option<CommitComposer>,
+    transfers: Vec<Transfer>,
+    /// Repos that newly fell behind their upstream or failed a background
+    /// fetch, until a key is pressed in this panel
+    alerts: Vec<(PathBuf, String)>,
 }
 
 impl GitPanel {
-    pub fn new() -> Self {
+    pub fn new(config: &GitConfig) -> Self {
         Self {
+            config: config.clone(),
             snapshot: GitSnapshot::default(),
+            listed: HashMap::new(),
+            sort: config.sort,
+            selected: 0,
+            show_detail: false,
+            views: Vec::new(),
+            notice: None,
+            confirm: None,
+            composer: None,
+            transfers: Vec::new(),
+            alerts: Vec::new(),
         }
     }
-}
 
-impl Panel for GitPanel {
-    fn on_data(&mut self, data: &DataSnapshot) {
-        if let DataSnapshot::Git(snap) = data {
-            self.snapshot = snap.clone();
+    /// Status bar message for repos that fell behind or failed to fetch
+    /// since they were last looked at, e.g. `api fell 3 behind origin/main`.
+    pub fn notification(&self) -> Option<String> {
+        let messages: Vec<&str> = self.alerts.iter().map(|(_, m)| m.as_str()).collect();
+        match messages.as_slice() {
+            [] => None,
+            [one] => Some(one.to_string()),
+            many => Some(format!("{} repos: {}", many.len(), many.join("; "))),
         }
     }
 
-    fn draw(&self, f: &mut Frame, area: Rect) {
-        let header_cells = ["Repo", "Branch", "Modified", "Staged", "Untracked", "Ahead", "Behind"]
+    fn alert(&mut self, path: &Path, message: String) {
+        self.alerts.retain(|(p, _)| p != path);
+        self.alerts.push((path.to_path_buf(), message));
+    }
+
+    /// Note a repo whose background fetch started failing, or failed
+    /// differently than before.
+    fn check_fetch(&mut self, entry: &RepoEntry) {
+        let Some(error) = &entry.fetch_error else {
+            return;
+        };
+        let before = self.snapshot.repos.iter().find(|r| r.path == entry.path);
+        if before.is_some_and(|r| r.fetch_error.as_ref() != Some(error)) {
+            self.alert(
+                &entry.path,
+                format!("{}: fetch failed: {error}", entry.name),
+            );
+        }
+    }
+
+    /// Note a repo that was level with its upstream in its previous row and
+    /// now is behind it, typically after a background fetch.
+    fn check_behind(&mut self, entry: &RepoEntry) {
+        let Some(RepoStatus {
+            upstream: Upstream::Tracking { name, behind, .. },
+            ..
+        }) = entry.ok_status()
+        else {
+            return;
+        };
+        let was_level = self
+            .snapshot
+            .repos
             .iter()
-            .map(|h| {
-                Cell::from(*h).style(
-                    Style::default()
-                        .fg(Color::Yellow)
-                        .add_modifier(Modifier::BOLD),
-                )
+            .find(|r| r.path == entry.path)
+            .and_then(RepoEntry::ok_status)
+            .is_some_and(|before| {
+                matches!(&before.upstream, Upstream::Tracking { name: before, behind: 0, .. } if before == name)
             });
-        let header = Row::new(header_cells).height(1);
+        if was_level && *behind > 0 {
+            let message = format!("{} fell {behind} behind {name}", entry.name);
+            self.alert(&entry.path, message);
+        }
+    }
 
-        let rows: Vec<Row> = self
+    /// Enter on a healthy repo opens its files; on a failing one it toggles
+    /// the error details.
+    fn open_selected(&mut self) -> Option<Action> {
+        let entry = self.selected_repo()?;
+        if entry.ok_status().is_none() {
+            self.show_detail = !self.show_detail;
+            return None;
+        }
+        self.open(View::Files(FilesView::new(entry)))
+    }
+
+    fn open_log(&mut self) -> Option<Action> {
+        let entry = self.selected_repo().filter(|e| e.ok_status().is_some())?;
+        self.open(View::Log(Box::new(LogView::new(entry.path.clone()))))
+    }
+
+    fn open_branches(&mut self) -> Option<Action> {
+        let entry = self.selected_repo().filter(|e| e.ok_status().is_some())?;
+        self.open(View::Branches(BranchesView::new(entry.path.clone())))
+    }
+
+    fn open_stashes(&mut self) -> Option<Action> {
+        let entry = self.selected_repo().filter(|e| e.ok_status().is_some())?;
+        self.open(View::Stash(StashView::new(entry.path.clone())))
+    }
+
+    fn open_worktrees(&mut self) -> Option<Action> {
+        let entry = self.selected_repo().filter(|e| e.ok_status().is_some())?;
+        self.open(View::Worktrees(WorktreesView::new(entry.path.clone())))
+    }
+
+    /// Stale branches across every repo that could be read.
+    fn open_stale(&mut self) -> Option<Action> {
+        let repos: Vec<PathBuf> = self
             .snapshot
             .repos
             .iter()
-            .map(|repo| {
-                let cells = vec![
-                    Cell::from(repo.name.clone()),
-                    Cell::from(repo.branch.clone()).style(Style::default().fg(Color::Cyan)),
-                    Cell::from(repo.modified.to_string()).style(if repo.modified > 0 {
-                        Style::default().fg(Color::Red)
-                    } else {
-                        Style::default().fg(Color::Green)
-                    }),
-                    Cell::from(repo.staged.to_string()).style(if repo.staged > 0 {
-                        Style::default().fg(Color::Yellow)
-                    } else {
-                        Style::default().fg(Color::Green)
-                    }),
-                    Cell::from(repo.untracked.to_string()).style(if repo.untracked > 0 {
-                        Style::default().fg(Color::Magenta)
-                    } else {
-                        Style::default().fg(Color::Green)
-                    }),
-                    Cell::from(repo.ahead.to_string()),
-                    Cell::from(repo.behind.to_string()),
-                ];
-                Row::new(cells)
-            })
+            .filter(|e| e.ok_status().is_some())
+            .map(|e| e.path.clone())
             .collect();
+        if repos.is_empty() {
+            return None;
+        }
+        self.open(View::Stale(StaleView::new(repos)))
+    }
+
+    fn open_submodules(&mut self) -> Option<Action> {
+        let entry = self.selected_repo().filter(|e| e.ok_status().is_some())?;
+        self.open(View::Submodules(SubmodulesView::new(entry.path.clone())))
+    }
+
+    fn open(&mut self, view: View) -> Option<Action> {
+        let request = view.request();
+        self.views.push(view);
+        Some(Action::Git(request))
+    }
+
+    /// Run `op`, reloading every open view afterwards so counts and diffs
+    /// update straight away.
+    fn apply(&self, repo: PathBuf, op: IndexOp) -> Action {
+        Action::Git(GitRequest::Index {
+            repo,
+            op,
+            reload: self.views.iter().map(View::request).collect(),
+        })
+    }
+
+    /// Run a branch `op`, reloading open views like `apply`.
+    fn branch(&self, repo: PathBuf, op: BranchOp) -> Action {
+        Action::Git(GitRequest::Branch {
+            repo,
+            op,
+            reload: self.views.iter().map(View::request).collect(),
+        })
+    }
+
+    /// Run a stash `op`, reloading open views like `apply`.
+    fn stash(&self, repo: PathBuf, op: StashOp) -> Action {
+        Action::Git(GitRequest::Stash {
+            repo,
+            op,
+            reload: self.views.iter().map(View::request).collect(),
+        })
+    }
+
+    /// Add or prune a worktree, reloading open views like `apply`.
+    fn worktree(&self, repo: PathBuf, op: WorktreeOp) -> Action {
+        Action::Git(GitRequest::Worktree {
+            repo,
+            op,
+            reload: self.views.iter().map(View::request).collect(),
+        })
+    }
+
+    /// Init and update submodules, reloading open views like `apply`.
+    fn submodule(&self, repo: PathBuf, op: SubmoduleOp) -> Action {
+        Action::Git(GitRequest::Submodule {
+            repo,
+            op,
+            reload: self.views.iter().map(View::request).collect(),
+        })
+    }
+
+    /// Delete stale branches, reloading open views like `apply`.
+    fn delete_branches(&self, branches: Vec<BranchRef>) -> Action {
+        Action::Git(GitRequest::DeleteBranches {
+            branches,
+            reload: self.views.iter().map(View::request).collect(),
+        })
+    }
+
+    /// Fetch, pull or push the selected repo; one transfer per repo at a time.
+    fn start_remote(&mut self, op: RemoteOp) -> Option<Action> {
+        let entry = self.selected_repo().filter(|e| e.ok_status().is_some())?;
+        if let Some(running) = self.transfers.iter().find(|t| t.repo == entry.path) {
+            self.notice = Some(Notice::Error(format!(
+                "{} is already running a {}",
+                entry.name,
+                running.op.describe()
+            )));
+            return None;
+        }
+        let repo = entry.path.clone();
+        self.transfers.push(Transfer {
+            repo: repo.clone(),
+            name: entry.name.clone(),
+            op,
+            progress: None,
+        });
+        Some(Action::GitRemote {
+            repo,
+            op,
+            reload: self.views.iter().map(View::request).collect(),
+        })
+    }
+
+    fn cancel_remote(&self) -> Option<Action> {
+        let entry = self.selected_repo()?;
+        self.transfers
+            .iter()
+            .any(|t| t.repo == entry.path)
+            .then(|| Action::CancelGitRemote(entry.path.clone()))
+    }
+
+    /// Repo the `c` key commits to: the open view's, else the selected row's.
+    fn open_composer(&mut self) -> Option<Action> {
+        let repo = match self.views.last() {
+            Some(view) => view.repo()?.to_path_buf(),
+            None => self
+                .selected_repo()
+                .filter(|e| e.ok_status().is_some())?
+                .path
+                .clone(),
+        };
+        let template = self.config.commit_template(&repo);
+        self.composer = Some(CommitComposer::new(repo.clone(), template));
+        Some(Action::Git(GitRequest::PrepareCommit(repo)))
+    }
+
+    fn composer_key(&mut self, key: KeyEvent) -> Option<Action> {
+        let composer = self.composer.as_mut()?;
+        match composer.handle_key(key) {
+            ComposerKey::Stay => None,
+            ComposerKey::Cancel => {
+                self.composer = None;
+                None
+            }
+            ComposerKey::Commit { message, amend } => Some(Action::Git(GitRequest::Commit {
+                repo: composer.repo().to_path_buf(),
+                message,
+                amend,
+                reload: self.views.iter().map(View::request).collect(),
+            })),
+        }
+    }
+
+    /// An index operation or commit finished: refresh the repo's row and the
+    /// open views from the results that came back with it.
+    fn on_repo_changed(&mut self, repo: &Path, entry: &RepoEntry, reloaded: &[GitEvent]) {
+        if let Some(row) = self.snapshot.repos.iter_mut().find(|r| r.path == repo) {
+            // Only the source knows how background fetches went
+            let fetch_error = row.fetch_error.take();
+            *row = entry.clone();
+            row.fetch_error = fetch_error;
+        }
+        self.resort();
+        for event in reloaded {
+            for view in &mut self.views {
+                view.on_event(event);
+            }
+        }
+    }
+
+    /// Put the rows back in `sort` order, keeping the cursor on the same repo.
+    fn resort(&mut self) {
+        let selected = self.selected_repo().map(|e| e.path.clone());
+        let listed = &self.listed;
+        let repos = &mut self.snapshot.repos;
+        // Repos without a commit go last whichever way the rows are sorted
+        match self.sort {
+            GitSort::Listed => {
+                repos.sort_by_key(|e| listed.get(&e.path).copied().unwrap_or(usize::MAX))
+            }
+            GitSort::Age => repos.sort_by_key(|e| Reverse(last_commit(e).map(|c| c.time))),
+            GitSort::Changed => {
+                repos.sort_by_key(|e| Reverse(e.ok_status().and_then(|r| r.last_change)))
+            }
+            GitSort::Author => repos.sort_by_cached_key(|e| {
+                let author = last_commit(e).map(|c| c.author.to_lowercase());
+                (author.is_none(), author)
+            }),
+            GitSort::Subject => repos.sort_by_cached_key(|e| {
+                let subject = last_commit(e).map(|c| c.subject.to_lowercase());
+                (subject.is_none(), subject)
+            }),
+        }
+        if let Some(i) = selected.and_then(|path| repos.iter().position(|e| e.path == path)) {
+            self.selected = i;
+        }
+    }
+
+    fn cycle_sort(&mut self) {
+        self.sort = match self.sort {
+            GitSort::Listed => GitSort::Age,
+            GitSort::Age => GitSort::Changed,
+            GitSort::Changed => GitSort::Author,
+            GitSort::Author => GitSort::Subject,
+            GitSort::Subject => GitSort::Listed,
+        };
+        self.resort();
+    }
+
+    fn selected_repo(&self) -> Option<&RepoEntry> {
+        self.snapshot.repos.get(self.selected)
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let len = self.snapshot.repos.len();
+        self.selected = self
+            .selected
+            .saturating_add_signed(delta)
+            .min(len.saturating_sub(1));
+    }
+
+    fn draw_main(&self, f: &mut Frame, area: Rect) {
+        if let Some(view) = self.views.last() {
+            view.draw(f, area);
+            return;
+        }
+
+        let Some(entry) = self.selected_repo().filter(|_| self.show_detail) else {
+            self.draw_table(f, area);
+            return;
+        };
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(5), Constraint::Length(7)])
+            .split(area);
+        self.draw_table(f, chunks[0]);
+        self.draw_detail(f, chunks[1], entry);
+    }
+
+    fn draw_table(&self, f: &mut Frame, area: Rect) {
+        let columns = &self.config.columns;
+        let mut headers = vec![
+            "Repo",
+            "Branch",
+            "State",
+            "Upstream",
+            "Modified",
+            "Staged",
+            "Untracked",
+            "Ahead",
+            "Behind",
+            "Stash",
+            "Fetched",
+        ];
+        headers.extend(columns.iter().map(|c| column_header(*c)));
+        let header_cells = headers.iter().map(|h| {
+            Cell::from(*h).style(
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )
+        });
+        let header = Row::new(header_cells).height(1);
+
+        // Linked worktrees and submodules sit under their repo's row, so the
+        // selected repo's row index counts the child rows above it
+        let mut rows: Vec<Row> = Vec::new();
+        let mut selected_row = 0;
+        for (i, entry) in self.snapshot.repos.iter().enumerate() {
+            if i == self.selected {
+                selected_row = rows.len();
+            }
+            let name = if entry.scanning {
+                format!("{} {}", spinner(), entry.name)
+            } else {
+                entry.name.clone()
+            };
+            rows.push(match &entry.status {
+                Some(Ok(repo)) => status_row(&name, repo, entry.fetch_error.is_some(), columns),
+                Some(Err(e)) => error_row(&name, e),
+                None => scanning_row(&name),
+            });
+            let worktrees = entry.ok_status().map_or(&[][..], |r| &r.worktrees);
+            rows.extend(
+                worktrees
+                    .iter()
+                    // Monitored in their own right already
+                    .filter(|w| !self.snapshot.repos.iter().any(|r| r.path == w.path))
+                    .map(nested_worktree_row),
+            );
+            let submodules = entry.ok_status().map_or(&[][..], |r| &r.submodules);
+            rows.extend(submodules.iter().map(nested_submodule_row));
+        }
 
-        let widths = [
-            ratatui::layout::Constraint::Min(15),
-            ratatui::layout::Constraint::Min(15),
-            ratatui::layout::Constraint::Length(10),
-            ratatui::layout::Constraint::Length(8),
-            ratatui::layout::Constraint::Length(11),
-            ratatui::layout::Constraint::Length(7),
-            ratatui::layout::Constraint::Length(8),
+        let mut widths = vec![
+            Constraint::Min(15),
+            Constraint::Min(15),
+            Constraint::Length(22),
+            Constraint::Min(15),
+            Constraint::Length(10),
+            Constraint::Length(8),
+            Constraint::Length(11),
+            Constraint::Length(7),
+            Constraint::Length(8),
+            Constraint::Length(6),
+            Constraint::Length(15),
         ];
+        widths.extend(columns.iter().map(|c| column_width(*c)));
 
+        let mut title = vec![Span::raw(match sort_label(self.sort) {
+            Some(order) => format!(" Git Repositories ({order}) "),
+            None => " Git Repositories ".to_string(),
+        })];
+        if let Some(error) = &self.snapshot.scan_error {
+            title.push(Span::styled(
+                format!("{error} "),
+                Style::default().fg(Color::Red),
+            ));
+        }
+        let title = Line::from(title);
         let table = Table::new(rows, widths)
             .header(header)
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .title(" Git Repositories "),
-            )
-            .style(Style::default().fg(Color::White));
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .style(Style::default().fg(Color::White))
+            .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        let mut state = TableState::default().with_selected(Some(selected_row));
+        f.render_stateful_widget(table, area, &mut state);
+    }
+
+    fn draw_detail(&self, f: &mut Frame, area: Rect, entry: &RepoEntry) {
+        let label = |text: &'static str| Span::styled(text, Style::default().fg(Color::Yellow));
+
+        let mut lines = vec![Line::from(vec![
+            label("Path:     "),
+            Span::raw(entry.path.display().to_string()),
+        ])];
+        match &entry.status {
+            None => lines.push(Line::from(vec![
+                label("Status:   "),
+                Span::styled("scanning…", Style::default().fg(Color::DarkGray)),
+            ])),
+            Some(Ok(repo)) => {
+                lines.push(Line::from(vec![
+                    label("HEAD:     "),
+                    Span::raw(repo.head.label()),
+                ]));
+                lines.push(Line::from(vec![
+                    label("Upstream: "),
+                    Span::raw(repo.upstream.label()),
+                ]));
+                if let Some(ignored) = repo.ignored {
+                    lines.push(Line::from(vec![
+                        label("Ignored:  "),
+                        Span::raw(format!("{ignored} path(s)")),
+                    ]));
+                }
+                if let Some(error) = &entry.fetch_error {
+                    lines.push(Line::from(vec![
+                        label("Fetch:    "),
+                        Span::styled(error.clone(), Style::default().fg(Color::Red)),
+                    ]));
+                }
+                if let Some(state) = repo.state.label() {
+                    lines.push(Line::from(vec![
+                        label("State:    "),
+                        Span::styled(
+                            format!("{state}, {} conflicted path(s)", repo.conflicts),
+                            Style::default().fg(Color::Red),
+                        ),
+                    ]));
+                }
+            }
+            Some(Err(e)) => {
+                let error_style = Style::default().fg(Color::Red);
+                lines.push(Line::from(vec![
+                    label("Error:    "),
+                    Span::styled(e.message.clone(), error_style),
+                ]));
+                lines.push(Line::from(vec![
+                    label("Class:    "),
+                    Span::raw(format!("{:?}", e.class)),
+                ]));
+                lines.push(Line::from(vec![
+                    label("Code:     "),
+                    Span::raw(format!("{:?} ({})", e.code, e.raw_code)),
+                ]));
+            }
+        }
+
+        let detail = Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" {} ", entry.name)),
+        );
+        f.render_widget(detail, area);
+    }
+}
+
+fn last_commit(entry: &RepoEntry) -> Option<&LastCommit> {
+    entry.ok_status()?.last_commit.as_ref()
+}
+
+fn sort_label(sort: GitSort) -> Option<&'static str> {
+    match sort {
+        GitSort::Listed => None,
+        GitSort::Age => Some("newest commit first"),
+        GitSort::Changed => Some("latest change first"),
+        GitSort::Author => Some("by author"),
+        GitSort::Subject => Some("by subject"),
+    }
+}
+
+fn column_header(column: GitColumn) -> &'static str {
+    match column {
+        GitColumn::Commit => "Commit",
+        GitColumn::Author => "Author",
+        GitColumn::Age => "Committed",
+        GitColumn::Subject => "Subject",
+        GitColumn::Changed => "Changed",
+    }
+}
+
+fn column_width(column: GitColumn) -> Constraint {
+    match column {
+        GitColumn::Commit => Constraint::Length(8),
+        GitColumn::Author => Constraint::Length(16),
+        GitColumn::Age | GitColumn::Changed => Constraint::Length(15),
+        GitColumn::Subject => Constraint::Min(20),
+    }
+}
+
+fn column_cell<'a>(column: GitColumn, repo: &RepoStatus) -> Cell<'a> {
+    let dim = Style::default().fg(Color::DarkGray);
+    let age = |time: Option<i64>| match time {
+        Some(at) => {
+            let age = (chrono::Utc::now().timestamp() - at).max(0) as u64;
+            Cell::from(format_age(age))
+        }
+        None => Cell::from("-").style(dim),
+    };
+    let commit = repo.last_commit.as_ref();
+    match column {
+        GitColumn::Commit => match commit {
+            Some(c) => Cell::from(c.id.clone()).style(Style::default().fg(Color::Yellow)),
+            None => Cell::from("-").style(dim),
+        },
+        GitColumn::Author => Cell::from(commit.map(|c| c.author.clone()).unwrap_or_default()),
+        GitColumn::Age => age(commit.map(|c| c.time)),
+        GitColumn::Subject => Cell::from(commit.map(|c| c.subject.clone()).unwrap_or_default()),
+        GitColumn::Changed => age(repo.last_change),
+    }
+}
+
+fn status_row<'a>(
+    name: &str,
+    repo: &RepoStatus,
+    fetch_failed: bool,
+    columns: &[GitColumn],
+) -> Row<'a> {
+    let (upstream_style, ahead, behind) = match &repo.upstream {
+        Upstream::Tracking { ahead, behind, .. } => (
+            Style::default().fg(Color::Cyan),
+            ahead.to_string(),
+            behind.to_string(),
+        ),
+        Upstream::Gone { .. } => (
+            Style::default().fg(Color::Red),
+            "-".to_string(),
+            "-".to_string(),
+        ),
+        Upstream::None => (
+            Style::default().fg(Color::DarkGray),
+            "-".to_string(),
+            "-".to_string(),
+        ),
+    };
+    let mut cells = vec![
+        Cell::from(name.to_string()),
+        Cell::from(repo.head.label()).style(Style::default().fg(Color::Cyan)),
+        state_cell(repo),
+        Cell::from(repo.upstream.label()).style(upstream_style),
+        Cell::from(repo.modified.to_string()).style(if repo.modified > 0 {
+            Style::default().fg(Color::Red)
+        } else {
+            Style::default().fg(Color::Green)
+        }),
+        Cell::from(repo.staged.to_string()).style(if repo.staged > 0 {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default().fg(Color::Green)
+        }),
+        Cell::from(repo.untracked.to_string()).style(if repo.untracked > 0 {
+            Style::default().fg(Color::Magenta)
+        } else {
+            Style::default().fg(Color::Green)
+        }),
+        Cell::from(ahead),
+        Cell::from(behind),
+        Cell::from(repo.stashes.to_string()).style(if repo.stashes > 0 {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        }),
+        fetched_cell(repo, fetch_failed),
+    ];
+    cells.extend(columns.iter().map(|c| column_cell(*c, repo)));
+    Row::new(cells)
+}
+
+/// A linked worktree under its repo's row, with its path in the Upstream
+/// column.
+fn nested_worktree_row(worktree: &WorktreeInfo) -> Row<'static> {
+    let dim = Style::default().fg(Color::DarkGray);
+    Row::new(vec![
+        Cell::from(format!("  └ {}", worktree.name)).style(dim),
+        worktree_branch(worktree),
+        worktree_state(worktree),
+        Cell::from(worktree.path.display().to_string()).style(dim),
+        Cell::from(worktree.modified.to_string()),
+        Cell::from(worktree.staged.to_string()),
+        Cell::from(worktree.untracked.to_string()),
+    ])
+}
+
+/// A submodule under its repo's row: the checked-out commit in the Branch
+/// column and the recorded one in the Upstream column.
+fn nested_submodule_row(submodule: &SubmoduleInfo) -> Row<'static> {
+    let dim = Style::default().fg(Color::DarkGray);
+    Row::new(vec![
+        Cell::from(format!("  └ {}", submodule.path)).style(dim),
+        checked_out(submodule),
+        submodule_state(submodule),
+        Cell::from(match &submodule.recorded {
+            Some(id) => format!("recorded {id}"),
+            None => "not recorded".to_string(),
+        })
+        .style(dim),
+        Cell::from(submodule.modified.to_string()),
+        Cell::from(submodule.staged.to_string()),
+        Cell::from(submodule.untracked.to_string()),
+    ])
+}
 
-        f.render_widget(table, area);
+/// Age of the last fetch, or `failed` when the latest background fetch
+/// didn't go through; the status bar says why when it starts failing.
+fn fetched_cell<'a>(repo: &RepoStatus, failed: bool) -> Cell<'a> {
+    if failed {
+        return Cell::from("failed").style(Style::default().fg(Color::Red));
     }
+    let style = Style::default().fg(Color::DarkGray);
+    match repo.last_fetched {
+        Some(at) => {
+            let age = (chrono::Utc::now().timestamp() - at).max(0) as u64;
+            Cell::from(format_age(age)).style(style)
+        }
+        None => Cell::from("never").style(style),
+    }
+}
+
+/// Badge for an operation left in progress and any unresolved conflicts,
+/// e.g. `REBASE-i 3/7 ⚠ 2`.
+fn state_cell<'a>(repo: &RepoStatus) -> Cell<'a> {
+    let mut badge = repo.state.label().unwrap_or_default();
+    if repo.conflicts > 0 {
+        if !badge.is_empty() {
+            badge.push(' ');
+        }
+        badge.push_str(&format!("⚠ {}", repo.conflicts));
+    }
+    if badge.is_empty() {
+        return Cell::from("");
+    }
+    Cell::from(format!(" {badge} ")).style(
+        Style::default()
+            .fg(Color::White)
+            .bg(Color::Red)
+            .add_modifier(Modifier::BOLD),
+    )
+}
+
+fn scanning_row<'a>(name: &str) -> Row<'a> {
+    Row::new(vec![
+        Cell::from(name.to_string()),
+        Cell::from("scanning…").style(Style::default().fg(Color::DarkGray)),
+    ])
+}
+
+/// Frame of the busy indicator shown next to repos still being read.
+fn spinner() -> char {
+    const FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+    let tick = chrono::Utc::now().timestamp_millis() / 100;
+    FRAMES[tick.rem_euclid(FRAMES.len() as i64) as usize]
+}
+
+fn error_row<'a>(name: &str, error: &RepoError) -> Row<'a> {
+    let error_style = Style::default().fg(Color::Red);
+    Row::new(vec![
+        Cell::from(name.to_string()).style(error_style),
+        Cell::from("⚠ error").style(error_style.add_modifier(Modifier::BOLD)),
+        Cell::from(""),
+        Cell::from(error.message.clone()).style(error_style),
+    ])
+}
+
+impl Panel for GitPanel {
+    fn on_data(&mut self, data: &DataSnapshot) {
+        match data {
+            DataSnapshot::Git(snap) => {
+                for entry in &snap.repos {
+                    self.check_behind(entry);
+                    self.check_fetch(entry);
+                }
+                self.snapshot = snap.clone();
+                self.listed = snap
+                    .repos
+                    .iter()
+                    .enumerate()
+                    .map(|(i, e)| (e.path.clone(), i))
+                    .collect();
+                self.resort();
+                self.move_selection(0);
+            }
+            DataSnapshot::GitRepo(entry) => {
+                self.check_behind(entry);
+                self.check_fetch(entry);
+                if let Some(row) = self
+                    .snapshot
+                    .repos
+                    .iter_mut()
+                    .find(|r| r.path == entry.path)
+                {
+                    *row = (**entry).clone();
+                }
+                self.resort();
+            }
+            DataSnapshot::GitEvent(GitEvent::Index {
+                repo,
+                op,
+                result,
+                entry,
+                reloaded,
+            }) => {
+                if let Err(e) = result {
+                    self.notice = Some(Notice::Error(format!(
+                        "Failed to {}: {}",
+                        op.describe(),
+                        e.message
+                    )));
+                }
+                self.on_repo_changed(repo, entry, reloaded);
+            }
+            DataSnapshot::GitEvent(GitEvent::Branch {
+                repo,
+                op,
+                result,
+                entry,
+                reloaded,
+            }) => {
+                match result {
+                    Ok(Some(note)) => {
+                        self.notice = Some(Notice::Info(format!(
+                            "{}: {}; {note}",
+                            entry.name,
+                            op.describe()
+                        )));
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        self.notice = Some(Notice::Error(format!(
+                            "Failed to {}: {}",
+                            op.describe(),
+                            e.message
+                        )));
+                    }
+                }
+                self.on_repo_changed(repo, entry, reloaded);
+            }
+            DataSnapshot::GitEvent(GitEvent::Worktree {
+                repo,
+                op,
+                result,
+                entry,
+                reloaded,
+            }) => {
+                self.notice = Some(match result {
+                    Ok(()) => Notice::Info(format!("{}: {}", entry.name, op.describe())),
+                    Err(e) => Notice::Error(format!("Failed to {}: {}", op.describe(), e.message)),
+                });
+                self.on_repo_changed(repo, entry, reloaded);
+            }
+            DataSnapshot::GitEvent(GitEvent::Submodule {
+                repo,
+                op,
+                result,
+                entry,
+                reloaded,
+            }) => {
+                self.notice = Some(match result {
+                    Ok(()) => Notice::Info(format!("{}: {}", entry.name, op.describe())),
+                    Err(e) => Notice::Error(format!("Failed to {}: {}", op.describe(), e.message)),
+                });
+                self.on_repo_changed(repo, entry, reloaded);
+            }
+            DataSnapshot::GitEvent(
+                event @ GitEvent::DeletedBranches {
+                    deleted,
+                    recorded,
+                    reloaded,
+                },
+            ) => {
+                let kept = deleted.iter().filter(|d| d.result.is_err()).count();
+                let count = deleted.len() - kept;
+                let mut message = format!("Deleted {count} branch(es)");
+                if kept > 0 {
+                    message.push_str(&format!(", kept {kept}"));
+                }
+                match recorded {
+                    Ok(log) if count > 0 => {
+                        message.push_str(&format!("; restore commands in {}", log.display()))
+                    }
+                    Ok(_) => {}
+                    Err(e) => message.push_str(&format!(
+                        "; couldn't save the restore commands ({e}), copy them from the list"
+                    )),
+                }
+                self.notice = Some(if kept > 0 || recorded.is_err() {
+                    Notice::Error(message)
+                } else {
+                    Notice::Info(message)
+                });
+                for event in std::iter::once(event).chain(reloaded) {
+                    for view in &mut self.views {
+                        view.on_event(event);
+                    }
+                }
+            }
+            DataSnapshot::GitEvent(GitEvent::Stash {
+                repo,
+                op,
+                result,
+                entry,
+                reloaded,
+            }) => {
+                self.notice = match result {
+                    Ok(0) => None,
+                    Ok(conflicts) => Some(Notice::Error(format!(
+                        "{} left {conflicts} conflicted path(s); resolve them in the files view{}",
+                        op.describe(),
+                        if matches!(op, StashOp::Pop(_)) {
+                            ", the stash was kept"
+                        } else {
+                            ""
+                        }
+                    ))),
+                    Err(e) => Some(Notice::Error(format!(
+                        "Failed to {}: {}",
+                        op.describe(),
+                        e.message
+                    ))),
+                };
+                self.on_repo_changed(repo, entry, reloaded);
+            }
+            DataSnapshot::GitEvent(GitEvent::RemoteProgress { repo, progress, .. }) => {
+                if let Some(transfer) = self.transfers.iter_mut().find(|t| t.repo == *repo) {
+                    transfer.progress = Some(*progress);
+                }
+            }
+            DataSnapshot::GitEvent(GitEvent::Remote {
+                repo,
+                op,
+                result,
+                entry,
+                reloaded,
+            }) => {
+                self.transfers.retain(|t| t.repo != *repo);
+                self.notice = Some(match result {
+                    Ok(summary) => Notice::Info(format!("{}: {summary}", entry.name)),
+                    Err(e) => Notice::Error(format!(
+                        "Failed to {} {}: {}",
+                        op.describe(),
+                        entry.name,
+                        e.message
+                    )),
+                });
+                self.on_repo_changed(repo, entry, reloaded);
+            }
+            DataSnapshot::GitEvent(
+                event @ GitEvent::Committed {
+                    repo,
+                    result,
+                    entry,
+                    reloaded,
+                },
+            ) => {
+                match result {
+                    Ok(id) => {
+                        self.composer = None;
+                        self.notice = Some(Notice::Info(format!("Committed {id}")));
+                    }
+                    Err(_) => {
+                        if let Some(composer) = &mut self.composer {
+                            composer.on_event(event);
+                        }
+                    }
+                }
+                self.on_repo_changed(repo, entry, reloaded);
+            }
+            DataSnapshot::GitEvent(event) => {
+                if let Some(composer) = &mut self.composer {
+                    composer.on_event(event);
+                }
+                for view in &mut self.views {
+                    view.on_event(event);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn draw(&self, f: &mut Frame, area: Rect) {
+        let mut constraints = vec![Constraint::Min(3)];
+        if !self.transfers.is_empty() {
+            constraints.push(Constraint::Length(self.transfers.len() as u16));
+        }
+        if self.notice.is_some() {
+            constraints.push(Constraint::Length(1));
+        }
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(area);
+
+        self.draw_main(f, chunks[0]);
+        let mut next = 1;
+        if !self.transfers.is_empty() {
+            let lines: Vec<Line> = self.transfers.iter().map(transfer_line).collect();
+            f.render_widget(Paragraph::new(lines), chunks[next]);
+            next += 1;
+        }
+        if let Some(notice) = &self.notice {
+            let (text, color) = match notice {
+                Notice::Info(text) => (text, Color::Green),
+                Notice::Error(text) => (text, Color::Red),
+            };
+            f.render_widget(
+                Paragraph::new(Span::styled(text.as_str(), Style::default().fg(color))),
+                chunks[next],
+            );
+        }
+        if let Some((_, op)) = &self.confirm {
+            draw_confirm(f, area, op);
+        }
+        if let Some(composer) = &self.composer {
+            composer.draw(f, area);
+        }
+    }
+
+    fn captures_input(&self) -> bool {
+        self.confirm.is_some()
+            || self.composer.is_some()
+            || self.views.last().is_some_and(View::captures_input)
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Option<Action> {
+        if self.composer.is_some() {
+            return self.composer_key(key);
+        }
+        if let Some((repo, op)) = self.confirm.take() {
+            return (key.code == KeyCode::Char('y')).then(|| self.apply(repo, op));
+        }
+        self.notice = None;
+        self.alerts.clear();
+        let typing = self.views.last().is_some_and(View::captures_input);
+        if key.code == KeyCode::Char('c') && !typing {
+            return self.open_composer();
+        }
+
+        if let Some(view) = self.views.last_mut() {
+            return match view.handle_key(key) {
+                Nav::Stay => None,
+                Nav::Back => {
+                    self.views.pop();
+                    None
+                }
+                Nav::Open(view) => self.open(view),
+                Nav::Run(action) => Some(action),
+                Nav::Apply { repo, op } if op.is_destructive() => {
+                    self.confirm = Some((repo, op));
+                    None
+                }
+                Nav::Apply { repo, op } => Some(self.apply(repo, op)),
+                Nav::Branch { repo, op } => Some(self.branch(repo, op)),
+                Nav::Stash { repo, op } => Some(self.stash(repo, op)),
+                Nav::Worktree { repo, op } => Some(self.worktree(repo, op)),
+                Nav::Submodule { repo, op } => Some(self.submodule(repo, op)),
+                Nav::DeleteBranches(branches) => Some(self.delete_branches(branches)),
+            };
+        }
+
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::Enter => return self.open_selected(),
+            KeyCode::Char('L') => return self.open_log(),
+            KeyCode::Char('b') => return self.open_branches(),
+            KeyCode::Char('S') => return self.open_stashes(),
+            KeyCode::Char('w') => return self.open_worktrees(),
+            KeyCode::Char('m') => return self.open_submodules(),
+            KeyCode::Char('o') => self.cycle_sort(),
+            KeyCode::Char('B') => return self.open_stale(),
+            KeyCode::Char('f') => return self.start_remote(RemoteOp::Fetch),
+            KeyCode::Char('p') => return self.start_remote(RemoteOp::Pull),
+            KeyCode::Char('P') => return self.start_remote(RemoteOp::Push),
+            KeyCode::Char('x') => return self.cancel_remote(),
+            KeyCode::Esc => self.show_detail = false,
+            _ => {}
+        }
+        None
+    }
+
+    fn refresh(&mut self) -> Option<Action> {
+        self.views.last().map(|v| Action::Git(v.request()))
+    }
+}
+
+fn transfer_line(transfer: &Transfer) -> Line<'static> {
+    let mut text = format!("{} {}", transfer.op.verb(), transfer.name);
+    match &transfer.progress {
+        Some(p) if p.total > 0 => text.push_str(&format!(
+            ": {} {}% ({}/{}), {}",
+            p.phase.label(),
+            p.current * 100 / p.total,
+            p.current,
+            p.total,
+            format_bytes(p.bytes as u64)
+        )),
+        _ => text.push('…'),
+    }
+    Line::from(vec![
+        Span::styled(text, Style::default().fg(Color::Cyan)),
+        Span::styled("  x: cancel", Style::default().fg(Color::DarkGray)),
+    ])
+}
+
+fn draw_confirm(f: &mut Frame, area: Rect, op: &IndexOp) {
+    let popup = centered_rect(area, 60, 4);
+    f.render_widget(Clear, popup);
+    let text = vec![
+        Line::from(format!("Really {}? This cannot be undone.", op.describe())),
+        Line::from(vec![
+            Span::styled("y", Style::default().fg(Color::Yellow)),
+            Span::raw(": confirm  "),
+            Span::styled("any other key", Style::default().fg(Color::Yellow)),
+            Span::raw(": cancel"),
+        ]),
+    ];
+    let dialog = Paragraph::new(text).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Red))
+            .title(" Confirm "),
+    );
+    f.render_widget(dialog, popup);
 }
@@ -0,0 +1,394 @@
+use std::path::{Path, PathBuf};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use git2::Oid;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+use crate::source::git::branches::{BranchInfo, BranchList, BranchOp};
+use crate::source::git::{GitEvent, GitRequest, RepoError, Upstream};
+use crate::ui::format::format_age;
+use crate::ui::input::TextInput;
+
+use super::Nav;
+
+/// What the line under the branch table is asking for.
+enum Prompt {
+    /// Name for a new branch at `from`
+    Create {
+        from: Oid,
+        input: TextInput,
+    },
+    Rename {
+        from: String,
+        input: TextInput,
+    },
+    /// The tree is dirty; `y` stashes before checking out
+    Stash {
+        name: String,
+        remote: bool,
+    },
+    /// The branch isn't merged, or is the default one; `y` deletes it anyway
+    ForceDelete(String),
+}
+
+/// Local and remote branches of one repo.
+pub struct BranchesView {
+    repo: PathBuf,
+    list: Option<Result<BranchList, RepoError>>,
+    selected: usize,
+    prompt: Option<Prompt>,
+    status: Option<String>,
+}
+
+impl BranchesView {
+    pub fn new(repo: PathBuf) -> Self {
+        Self {
+            repo,
+            list: None,
+            selected: 0,
+            prompt: None,
+            status: None,
+        }
+    }
+
+    pub fn repo(&self) -> &Path {
+        &self.repo
+    }
+
+    pub fn request(&self) -> GitRequest {
+        GitRequest::Branches(self.repo.clone())
+    }
+
+    pub fn captures_input(&self) -> bool {
+        self.prompt.is_some()
+    }
+
+    fn branches(&self) -> &[BranchInfo] {
+        match &self.list {
+            Some(Ok(list)) => &list.branches,
+            _ => &[],
+        }
+    }
+
+    pub fn on_event(&mut self, event: &GitEvent) {
+        match event {
+            GitEvent::Branches { repo, result } if *repo == self.repo => {
+                self.list = Some(result.clone());
+                self.selected = self.selected.min(self.branches().len().saturating_sub(1));
+            }
+            _ => {}
+        }
+    }
+
+    fn op(&self, op: BranchOp) -> Nav {
+        Nav::Branch {
+            repo: self.repo.clone(),
+            op,
+        }
+    }
+
+    fn prompt_key(&mut self, prompt: Prompt, key: KeyEvent) -> Nav {
+        match prompt {
+            Prompt::Create { from, mut input } => match key.code {
+                KeyCode::Esc => {}
+                KeyCode::Enter => {
+                    let name = input.value().trim().to_string();
+                    if !name.is_empty() {
+                        return self.op(BranchOp::Create { name, from });
+                    }
+                }
+                _ => {
+                    input.handle_key(key);
+                    self.prompt = Some(Prompt::Create { from, input });
+                }
+            },
+            Prompt::Rename { from, mut input } => match key.code {
+                KeyCode::Esc => {}
+                KeyCode::Enter => {
+                    let to = input.value().trim().to_string();
+                    if !to.is_empty() && to != from {
+                        return self.op(BranchOp::Rename { from, to });
+                    }
+                }
+                _ => {
+                    input.handle_key(key);
+                    self.prompt = Some(Prompt::Rename { from, input });
+                }
+            },
+            Prompt::Stash { name, remote } => {
+                if key.code == KeyCode::Char('y') {
+                    return self.op(BranchOp::Checkout {
+                        name,
+                        remote,
+                        stash: true,
+                    });
+                }
+            }
+            Prompt::ForceDelete(name) => {
+                if key.code == KeyCode::Char('y') {
+                    return self.op(BranchOp::Delete { name, force: true });
+                }
+            }
+        }
+        Nav::Stay
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Nav {
+        if let Some(prompt) = self.prompt.take() {
+            return self.prompt_key(prompt, key);
+        }
+        self.status = None;
+
+        let dirty = matches!(&self.list, Some(Ok(list)) if list.dirty);
+        let Some(branch) = self.branches().get(self.selected).cloned() else {
+            return match key.code {
+                KeyCode::Esc | KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') => Nav::Back,
+                _ => Nav::Stay,
+            };
+        };
+
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(self.branches().len().saturating_sub(1));
+            }
+            KeyCode::Enter if branch.head => {
+                self.status = Some(format!("Already on {}", branch.name));
+            }
+            KeyCode::Enter if dirty => {
+                self.prompt = Some(Prompt::Stash {
+                    name: branch.name,
+                    remote: branch.remote,
+                });
+            }
+            KeyCode::Enter => {
+                return self.op(BranchOp::Checkout {
+                    name: branch.name,
+                    remote: branch.remote,
+                    stash: false,
+                });
+            }
+            KeyCode::Char('n') => {
+                self.prompt = Some(Prompt::Create {
+                    from: branch.target,
+                    input: TextInput::default(),
+                });
+            }
+            KeyCode::Char('R') | KeyCode::Char('d') if branch.remote => {
+                self.status = Some("Remote branches can only be changed by pushing".to_string());
+            }
+            KeyCode::Char('R') => {
+                self.prompt = Some(Prompt::Rename {
+                    input: TextInput::new(&branch.name),
+                    from: branch.name,
+                });
+            }
+            KeyCode::Char('d') if branch.head => {
+                self.status = Some("Can't delete the checked-out branch".to_string());
+            }
+            KeyCode::Char('d') if !branch.merged => {
+                self.prompt = Some(Prompt::ForceDelete(branch.name));
+            }
+            KeyCode::Char('d') => {
+                return self.op(BranchOp::Delete {
+                    name: branch.name,
+                    force: false,
+                });
+            }
+            KeyCode::Esc | KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') => {
+                return Nav::Back
+            }
+            _ => {}
+        }
+        Nav::Stay
+    }
+
+    pub fn draw(&self, f: &mut Frame, area: Rect) {
+        let mut constraints = vec![Constraint::Min(5)];
+        if self.prompt.is_some() {
+            constraints.push(Constraint::Length(3));
+        }
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(area);
+
+        self.draw_table(f, chunks[0]);
+        if let Some(prompt) = &self.prompt {
+            draw_prompt(f, chunks[1], prompt, self.default_name());
+        }
+    }
+
+    fn default_name(&self) -> &str {
+        match &self.list {
+            Some(Ok(list)) => list.default.as_deref().unwrap_or("the default branch"),
+            _ => "the default branch",
+        }
+    }
+
+    fn draw_table(&self, f: &mut Frame, area: Rect) {
+        let mut title = format!(" Branches — {} ", self.repo.display());
+        if let Some(Ok(list)) = &self.list {
+            if let Some(default) = &list.default {
+                title.push_str(&format!("(merged into {default}) "));
+            }
+        }
+        if let Some(status) = &self.status {
+            title.push_str(&format!("({status}) "));
+        }
+        let block = Block::default().borders(Borders::ALL).title(title);
+
+        let branches = match &self.list {
+            None => {
+                f.render_widget(Paragraph::new("Loading…").block(block), area);
+                return;
+            }
+            Some(Err(e)) => {
+                let msg = Span::styled(e.message.clone(), Style::default().fg(Color::Red));
+                f.render_widget(Paragraph::new(msg).block(block), area);
+                return;
+            }
+            Some(Ok(list)) => &list.branches,
+        };
+
+        let header = Row::new(
+            [
+                "",
+                "Branch",
+                "Upstream",
+                "Ahead",
+                "Behind",
+                "Last commit",
+                "Merged",
+                "Subject",
+            ]
+            .iter()
+            .map(|h| {
+                Cell::from(*h).style(
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )
+            }),
+        );
+
+        let now = chrono::Utc::now().timestamp();
+        let rows: Vec<Row> = branches.iter().map(|b| branch_row(b, now)).collect();
+
+        let widths = [
+            Constraint::Length(1),
+            Constraint::Min(15),
+            Constraint::Min(15),
+            Constraint::Length(6),
+            Constraint::Length(7),
+            Constraint::Length(15),
+            Constraint::Length(7),
+            Constraint::Min(20),
+        ];
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(block)
+            .style(Style::default().fg(Color::White))
+            .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        let mut state = TableState::default().with_selected(Some(self.selected));
+        f.render_stateful_widget(table, area, &mut state);
+    }
+}
+
+fn branch_row(branch: &BranchInfo, now: i64) -> Row<'static> {
+    let name_style = if branch.head {
+        Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD)
+    } else if branch.remote {
+        Style::default().fg(Color::Red)
+    } else {
+        Style::default().fg(Color::Green)
+    };
+    let (upstream_style, ahead, behind) = match &branch.upstream {
+        Upstream::Tracking { ahead, behind, .. } => (
+            Style::default().fg(Color::Cyan),
+            ahead.to_string(),
+            behind.to_string(),
+        ),
+        Upstream::Gone { .. } => (
+            Style::default().fg(Color::Red),
+            String::new(),
+            String::new(),
+        ),
+        Upstream::None => (
+            Style::default().fg(Color::DarkGray),
+            String::new(),
+            String::new(),
+        ),
+    };
+    let upstream = match branch.upstream {
+        Upstream::None if branch.remote => String::new(),
+        ref upstream => upstream.label(),
+    };
+
+    Row::new(vec![
+        Cell::from(if branch.head { "*" } else { "" }).style(name_style),
+        Cell::from(branch.name.clone()).style(name_style),
+        Cell::from(upstream).style(upstream_style),
+        Cell::from(ahead),
+        Cell::from(behind),
+        Cell::from(format_age((now - branch.time).max(0) as u64))
+            .style(Style::default().fg(Color::DarkGray)),
+        Cell::from(if branch.merged { "✓" } else { "" }).style(Style::default().fg(Color::Green)),
+        Cell::from(branch.summary.clone()),
+    ])
+}
+
+fn draw_prompt(f: &mut Frame, area: Rect, prompt: &Prompt, default: &str) {
+    let question = match prompt {
+        Prompt::Create { from, input } => {
+            let title = format!(
+                "New branch at {} (Enter: create, Esc: cancel)",
+                short(*from)
+            );
+            input.draw(f, area, &title);
+            return;
+        }
+        Prompt::Rename { from, input } => {
+            input.draw(
+                f,
+                area,
+                &format!("Rename {from} (Enter: rename, Esc: cancel)"),
+            );
+            return;
+        }
+        Prompt::Stash { name, .. } => {
+            format!("Uncommitted changes. Stash them and check out {name}?")
+        }
+        Prompt::ForceDelete(name) if name == default => {
+            format!("{name} is the default branch. Delete anyway?")
+        }
+        Prompt::ForceDelete(name) => format!("{name} is not merged into {default}. Delete anyway?"),
+    };
+    let text = Line::from(vec![
+        Span::raw(format!("{question}  ")),
+        Span::styled("y", Style::default().fg(Color::Yellow)),
+        Span::raw(": yes  "),
+        Span::styled("any other key", Style::default().fg(Color::Yellow)),
+        Span::raw(": cancel"),
+    ]);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Red))
+        .title(" Confirm ");
+    f.render_widget(Paragraph::new(text).block(block), area);
+}
+
+fn short(id: Oid) -> String {
+    id.to_string()[..7].to_string()
+}
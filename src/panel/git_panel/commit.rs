@@ -0,0 +1,316 @@
+use std::path::{Path, PathBuf};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::source::git::commit::CommitDraft;
+use crate::source::git::{GitEvent, RepoError};
+use crate::ui::layout::centered_rect;
+use crate::ui::textarea::TextArea;
+
+/// Subjects longer than this get a warning...
+const SUBJECT_SOFT_LIMIT: usize = 50;
+/// ...and longer than this can't be committed. Also the body line limit.
+const SUBJECT_HARD_LIMIT: usize = 72;
+
+/// Outcome of a key press in the composer.
+pub enum ComposerKey {
+    Stay,
+    Cancel,
+    Commit { message: String, amend: bool },
+}
+
+struct Problem {
+    text: String,
+    blocking: bool,
+}
+
+/// Modal editor for a commit message, opened with `c`.
+pub struct CommitComposer {
+    repo: PathBuf,
+    template: String,
+    editor: TextArea,
+    amend: bool,
+    draft: Option<Result<CommitDraft, RepoError>>,
+    /// Why the last commit attempt failed
+    error: Option<String>,
+    committing: bool,
+}
+
+impl CommitComposer {
+    pub fn new(repo: PathBuf, template: Option<&str>) -> Self {
+        let template = template.unwrap_or_default().to_string();
+        Self {
+            repo,
+            editor: TextArea::new(&template),
+            template,
+            amend: false,
+            draft: None,
+            error: None,
+            committing: false,
+        }
+    }
+
+    pub fn repo(&self) -> &Path {
+        &self.repo
+    }
+
+    pub fn on_event(&mut self, event: &GitEvent) {
+        match event {
+            GitEvent::CommitDraft { repo, result } if *repo == self.repo => {
+                self.draft = Some(result.clone());
+            }
+            GitEvent::Committed {
+                repo,
+                result: Err(e),
+                ..
+            } if *repo == self.repo => {
+                self.error = Some(e.message.clone());
+                self.committing = false;
+            }
+            _ => {}
+        }
+    }
+
+    fn draft(&self) -> Option<&CommitDraft> {
+        self.draft.as_ref().and_then(|d| d.as_ref().ok())
+    }
+
+    /// Row of the subject line: the first one that isn't a `#` comment.
+    fn subject_row(&self) -> Option<usize> {
+        self.editor.lines().iter().position(|l| !l.starts_with('#'))
+    }
+
+    fn problems(&self) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        let mut problem = |text: String, blocking| problems.push(Problem { text, blocking });
+
+        if let Some(Err(e)) = &self.draft {
+            problem(e.message.clone(), true);
+        }
+        if let Some(Err(e)) = self.draft().map(|d| &d.signature) {
+            problem(format!("No commit identity: {e}"), true);
+        }
+        if !self.amend && self.draft().is_some_and(|d| d.staged.is_empty()) {
+            problem("Nothing staged".to_string(), true);
+        }
+
+        let lines: Vec<&str> = self
+            .editor
+            .lines()
+            .iter()
+            .map(String::as_str)
+            .filter(|l| !l.starts_with('#'))
+            .collect();
+        let subject_len = lines.first().map_or(0, |s| s.trim_end().chars().count());
+        if subject_len == 0 {
+            problem("Subject line is empty".to_string(), true);
+        } else if subject_len > SUBJECT_HARD_LIMIT {
+            problem(
+                format!("Subject is {subject_len} chars; the limit is {SUBJECT_HARD_LIMIT}"),
+                true,
+            );
+        } else if subject_len > SUBJECT_SOFT_LIMIT {
+            problem(
+                format!("Subject is {subject_len} chars; {SUBJECT_SOFT_LIMIT} or fewer reads best"),
+                false,
+            );
+        }
+        if lines.get(1).is_some_and(|l| !l.trim().is_empty()) {
+            problem("Leave a blank line after the subject".to_string(), true);
+        }
+        if let Some(n) = lines
+            .iter()
+            .skip(2)
+            .position(|l| l.chars().count() > SUBJECT_HARD_LIMIT)
+        {
+            problem(
+                format!(
+                    "Body line {} is longer than {SUBJECT_HARD_LIMIT} chars",
+                    n + 3
+                ),
+                false,
+            );
+        }
+        problems
+    }
+
+    /// Amending starts from HEAD's message, unless the user already typed
+    /// something of their own.
+    fn toggle_amend(&mut self) {
+        self.amend = !self.amend;
+        let head_message = self.draft().and_then(|d| d.head_message.clone());
+        let text = self.editor.text();
+        if self.amend {
+            if let Some(message) = head_message.filter(|_| text.trim() == self.template.trim()) {
+                self.editor.set_text(&message);
+            }
+        } else if head_message.is_some_and(|m| m.trim() == text.trim()) {
+            self.editor.set_text(&self.template.clone());
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> ComposerKey {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return ComposerKey::Cancel,
+            KeyCode::Char('a') if ctrl => self.toggle_amend(),
+            KeyCode::Char('s') if ctrl => {
+                if self.committing || self.problems().iter().any(|p| p.blocking) {
+                    return ComposerKey::Stay;
+                }
+                self.committing = true;
+                self.error = None;
+                return ComposerKey::Commit {
+                    message: self.editor.text(),
+                    amend: self.amend,
+                };
+            }
+            _ => {
+                self.editor.handle_key(key);
+            }
+        }
+        ComposerKey::Stay
+    }
+
+    pub fn draw(&self, f: &mut Frame, area: Rect) {
+        let popup = centered_rect(area, 80, 22);
+        f.render_widget(Clear, popup);
+
+        let title = if self.amend {
+            " Amend commit "
+        } else {
+            " Commit "
+        };
+        let outer = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow))
+            .title(title);
+        let inner = outer.inner(popup);
+        f.render_widget(outer, popup);
+
+        let problems = self.problems();
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Min(5),
+                Constraint::Length((problems.len() + usize::from(self.error.is_some()) + 1) as u16),
+            ])
+            .split(inner);
+
+        let label = |text: &'static str| Span::styled(text, Style::default().fg(Color::Yellow));
+        let author = match self.draft().map(|d| &d.signature) {
+            Some(Ok(sig)) => Span::raw(sig.clone()),
+            Some(Err(_)) => Span::styled("unknown", Style::default().fg(Color::Red)),
+            None => Span::raw("…"),
+        };
+        f.render_widget(
+            Paragraph::new(Line::from(vec![
+                label("Repo "),
+                Span::raw(format!("{}  ", self.repo.display())),
+                label("Author "),
+                author,
+            ])),
+            chunks[0],
+        );
+
+        let body = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
+            .split(chunks[1]);
+        let subject_row = self.subject_row();
+        self.editor.draw(
+            f,
+            body[0],
+            Block::default().borders(Borders::ALL).title(" Message "),
+            |row, line| {
+                let style = if line.starts_with('#') {
+                    Style::default().fg(Color::DarkGray)
+                } else if Some(row) == subject_row {
+                    let len = line.chars().count();
+                    if len > SUBJECT_HARD_LIMIT {
+                        Style::default().fg(Color::Red)
+                    } else if len > SUBJECT_SOFT_LIMIT {
+                        Style::default().fg(Color::Yellow)
+                    } else {
+                        Style::default().add_modifier(Modifier::BOLD)
+                    }
+                } else {
+                    Style::default()
+                };
+                Line::from(Span::styled(line.to_string(), style))
+            },
+        );
+        self.draw_staged(f, body[1]);
+
+        let mut lines: Vec<Line> = problems
+            .iter()
+            .map(|p| {
+                let color = if p.blocking {
+                    Color::Red
+                } else {
+                    Color::Yellow
+                };
+                Line::from(Span::styled(p.text.clone(), Style::default().fg(color)))
+            })
+            .collect();
+        if let Some(error) = &self.error {
+            lines.push(Line::from(Span::styled(
+                format!("Commit failed: {error}"),
+                Style::default().fg(Color::Red),
+            )));
+        }
+        lines.push(Line::from(vec![
+            label("Ctrl+S"),
+            Span::raw(if self.committing {
+                ": committing…  "
+            } else {
+                ": commit  "
+            }),
+            label("Ctrl+A"),
+            Span::raw(": toggle amend  "),
+            label("Esc"),
+            Span::raw(": cancel"),
+        ]));
+        f.render_widget(Paragraph::new(lines), chunks[2]);
+    }
+
+    fn draw_staged(&self, f: &mut Frame, area: Rect) {
+        let lines: Vec<Line> = match &self.draft {
+            None => vec![Line::from("Loading…")],
+            Some(Err(_)) => Vec::new(),
+            Some(Ok(draft)) if draft.staged.is_empty() => {
+                vec![Line::from(Span::styled(
+                    "Nothing staged",
+                    Style::default().fg(Color::DarkGray),
+                ))]
+            }
+            Some(Ok(draft)) => draft
+                .staged
+                .iter()
+                .map(|file| {
+                    Line::from(vec![
+                        Span::styled(format!("{} ", file.code), Style::default().fg(Color::Green)),
+                        Span::raw(file.path.clone()),
+                    ])
+                })
+                .collect(),
+        };
+        let count = self.draft().map_or(0, |d| d.staged.len());
+        f.render_widget(
+            Paragraph::new(lines).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" Staged ({count}) ")),
+            ),
+            area,
+        );
+    }
+}
@@ -0,0 +1,270 @@
+use std::cell::Cell;
+use std::path::{Path, PathBuf};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+use crate::action::Action;
+use crate::source::git::diff::{DiffLine, DiffMode, FileDiff, LineKind};
+use crate::source::git::index::IndexOp;
+use crate::source::git::{GitEvent, GitRequest, RepoError};
+use crate::ui::format::format_bytes;
+
+use super::Nav;
+
+const ADDED_BG: Color = Color::Rgb(0, 55, 0);
+const ADDED_EMPHASIS_BG: Color = Color::Rgb(0, 105, 0);
+const REMOVED_BG: Color = Color::Rgb(65, 0, 0);
+const REMOVED_EMPHASIS_BG: Color = Color::Rgb(125, 0, 0);
+
+/// Scrollable diff of one file. The diff is rendered as a flat list of rows:
+/// each hunk's header followed by its lines.
+pub struct DiffView {
+    repo: PathBuf,
+    path: String,
+    mode: DiffMode,
+    diff: Option<Result<FileDiff, RepoError>>,
+    /// Row index of each hunk header.
+    hunk_rows: Vec<usize>,
+    scroll: usize,
+    /// Rows visible in the last draw, for paging.
+    page: Cell<usize>,
+}
+
+impl DiffView {
+    pub fn new(repo: PathBuf, path: String, mode: DiffMode) -> Self {
+        Self {
+            repo,
+            path,
+            mode,
+            diff: None,
+            hunk_rows: Vec::new(),
+            scroll: 0,
+            page: Cell::new(20),
+        }
+    }
+
+    pub fn repo(&self) -> &Path {
+        &self.repo
+    }
+
+    pub fn request(&self) -> GitRequest {
+        GitRequest::Diff {
+            repo: self.repo.clone(),
+            path: self.path.clone(),
+            mode: self.mode,
+        }
+    }
+
+    fn row_count(&self) -> usize {
+        match &self.diff {
+            Some(Ok(diff)) => diff.hunks.len() + diff.line_count(),
+            _ => 0,
+        }
+    }
+
+    pub fn on_event(&mut self, event: &GitEvent) {
+        match event {
+            GitEvent::Diff {
+                repo,
+                path,
+                mode,
+                result,
+            } if *repo == self.repo && *path == self.path && *mode == self.mode => {
+                self.hunk_rows.clear();
+                if let Ok(diff) = result {
+                    let mut row = 0;
+                    for hunk in &diff.hunks {
+                        self.hunk_rows.push(row);
+                        row += 1 + hunk.lines.len();
+                    }
+                }
+                self.diff = Some(result.clone());
+                self.scroll = self.scroll.min(self.row_count().saturating_sub(1));
+            }
+            _ => {}
+        }
+    }
+
+    fn current_hunk(&self) -> usize {
+        self.hunk_rows
+            .iter()
+            .rposition(|&row| row <= self.scroll)
+            .unwrap_or(0)
+    }
+
+    fn scroll_by(&mut self, delta: isize) {
+        self.scroll = self
+            .scroll
+            .saturating_add_signed(delta)
+            .min(self.row_count().saturating_sub(1));
+    }
+
+    fn jump_hunk(&mut self, forward: bool) {
+        let target = if forward {
+            self.hunk_rows.iter().find(|&&row| row > self.scroll)
+        } else {
+            self.hunk_rows.iter().rev().find(|&&row| row < self.scroll)
+        };
+        if let Some(&row) = target {
+            self.scroll = row;
+        }
+    }
+
+    /// Stage or discard the hunk at the top of the screen in unstaged mode,
+    /// unstage it in staged mode.
+    fn hunk_op(&self, key: KeyCode) -> Option<IndexOp> {
+        if self.hunk_rows.is_empty() {
+            return None;
+        }
+        let (path, hunk) = (self.path.clone(), self.current_hunk());
+        match (key, self.mode) {
+            (KeyCode::Char('s'), DiffMode::Unstaged) => Some(IndexOp::StageHunk { path, hunk }),
+            (KeyCode::Char('d'), DiffMode::Unstaged) => Some(IndexOp::DiscardHunk { path, hunk }),
+            (KeyCode::Char('u'), DiffMode::Staged) => Some(IndexOp::UnstageHunk { path, hunk }),
+            _ => None,
+        }
+    }
+
+    pub fn draw(&self, f: &mut Frame, area: Rect) {
+        let mut title = format!(" {} ({}) ", self.path, self.mode.label());
+        if !self.hunk_rows.is_empty() {
+            title.push_str(&format!(
+                "— hunk {}/{} ",
+                self.current_hunk() + 1,
+                self.hunk_rows.len()
+            ));
+        }
+        let block = Block::default().borders(Borders::ALL).title(title);
+        let height = block.inner(area).height as usize;
+        self.page.set(height.max(1));
+
+        let lines = match &self.diff {
+            None => vec![Line::from("Loading…")],
+            Some(Err(e)) => vec![Line::from(Span::styled(
+                e.message.clone(),
+                Style::default().fg(Color::Red),
+            ))],
+            Some(Ok(diff)) => {
+                if let Some(binary) = &diff.binary {
+                    vec![Line::from(format!(
+                        "Binary file differs ({} → {})",
+                        format_bytes(binary.old_size),
+                        format_bytes(binary.new_size)
+                    ))]
+                } else if diff.hunks.is_empty() {
+                    vec![Line::from(format!("No {} changes", self.mode.label()))]
+                } else {
+                    self.visible_lines(diff, height)
+                }
+            }
+        };
+
+        f.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    /// Only the rows on screen are turned into `Line`s, so huge diffs scroll
+    /// as cheaply as small ones.
+    fn visible_lines(&self, diff: &FileDiff, height: usize) -> Vec<Line<'static>> {
+        let rows = diff.hunks.iter().flat_map(|hunk| {
+            std::iter::once(None)
+                .chain(hunk.lines.iter().map(Some))
+                .zip(std::iter::repeat(hunk.header.as_str()))
+        });
+
+        let mut lines: Vec<Line> = rows
+            .skip(self.scroll)
+            .take(height)
+            .map(|(line, header)| match line {
+                None => Line::from(Span::styled(
+                    header.to_string(),
+                    Style::default().fg(Color::Cyan),
+                )),
+                Some(line) => diff_line(line),
+            })
+            .collect();
+
+        if diff.truncated && self.scroll + height >= self.row_count() && lines.len() < height {
+            lines.push(Line::from(Span::styled(
+                "… diff truncated",
+                Style::default().fg(Color::Yellow),
+            )));
+        }
+        lines
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Nav {
+        let page = self.page.get() as isize;
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.scroll_by(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll_by(1),
+            KeyCode::PageUp => self.scroll_by(-page),
+            KeyCode::PageDown | KeyCode::Char(' ') => self.scroll_by(page),
+            KeyCode::Home | KeyCode::Char('g') => self.scroll = 0,
+            KeyCode::End | KeyCode::Char('G') => self.scroll_by(isize::MAX),
+            KeyCode::Char('n') | KeyCode::Char(']') => self.jump_hunk(true),
+            KeyCode::Char('N') | KeyCode::Char('[') => self.jump_hunk(false),
+            KeyCode::Char('s') | KeyCode::Char('u') | KeyCode::Char('d') => {
+                if let Some(op) = self.hunk_op(key.code) {
+                    return Nav::Apply {
+                        repo: self.repo.clone(),
+                        op,
+                    };
+                }
+            }
+            KeyCode::Char('m') => {
+                self.mode = self.mode.toggle();
+                self.diff = None;
+                self.hunk_rows.clear();
+                self.scroll = 0;
+                return Nav::Run(Action::Git(self.request()));
+            }
+            KeyCode::Esc | KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') => {
+                return Nav::Back
+            }
+            _ => {}
+        }
+        Nav::Stay
+    }
+}
+
+fn lineno(n: Option<u32>) -> String {
+    n.map(|n| format!("{n:>5}"))
+        .unwrap_or_else(|| " ".repeat(5))
+}
+
+fn diff_line(line: &DiffLine) -> Line<'static> {
+    let (sign, bg, emphasis_bg) = match line.kind {
+        LineKind::Context => (' ', None, None),
+        LineKind::Added => ('+', Some(ADDED_BG), Some(ADDED_EMPHASIS_BG)),
+        LineKind::Removed => ('-', Some(REMOVED_BG), Some(REMOVED_EMPHASIS_BG)),
+    };
+    let base = bg.map_or(Style::default(), |bg| Style::default().bg(bg));
+
+    let mut spans = vec![
+        Span::styled(
+            format!("{} {} │", lineno(line.old_lineno), lineno(line.new_lineno)),
+            Style::default().fg(Color::DarkGray),
+        ),
+        Span::styled(sign.to_string(), base.add_modifier(Modifier::BOLD)),
+    ];
+    spans.extend(line.segments.iter().map(|segment| {
+        let mut style = base;
+        if let Some((r, g, b)) = segment.rgb {
+            style = style.fg(Color::Rgb(r, g, b));
+        }
+        if segment.emphasis {
+            if let Some(bg) = emphasis_bg {
+                style = style.bg(bg);
+            }
+        }
+        Span::styled(segment.text.clone(), style)
+    }));
+    Line::from(spans)
+}
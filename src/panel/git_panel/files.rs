@@ -0,0 +1,208 @@
+use std::path::{Path, PathBuf};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style},
+    text::Span,
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+use crate::source::git::diff::DiffMode;
+use crate::source::git::files::{FileChange, FileSection};
+use crate::source::git::index::IndexOp;
+use crate::source::git::{GitEvent, GitRequest, RepoEntry, RepoError};
+
+use super::diff::DiffView;
+use super::{Nav, View};
+
+/// `git status`-style listing of every changed file in one repo.
+pub struct FilesView {
+    repo: PathBuf,
+    name: String,
+    files: Option<Result<Vec<FileChange>, RepoError>>,
+    selected: usize,
+}
+
+impl FilesView {
+    pub fn new(entry: &RepoEntry) -> Self {
+        Self {
+            repo: entry.path.clone(),
+            name: entry.name.clone(),
+            files: None,
+            selected: 0,
+        }
+    }
+
+    pub fn repo(&self) -> &Path {
+        &self.repo
+    }
+
+    pub fn request(&self) -> GitRequest {
+        GitRequest::Files(self.repo.clone())
+    }
+
+    fn files(&self) -> &[FileChange] {
+        match &self.files {
+            Some(Ok(files)) => files,
+            _ => &[],
+        }
+    }
+
+    pub fn on_event(&mut self, event: &GitEvent) {
+        match event {
+            GitEvent::Files { repo, result } if *repo == self.repo => {
+                self.files = Some(result.clone());
+                self.selected = self.selected.min(self.files().len().saturating_sub(1));
+            }
+            _ => {}
+        }
+    }
+
+    pub fn draw(&self, f: &mut Frame, area: Rect) {
+        let title = format!(" {} — {} ", self.name, self.repo.display());
+        let block = Block::default().borders(Borders::ALL).title(title);
+
+        let files = match &self.files {
+            None => {
+                f.render_widget(Paragraph::new("Loading…").block(block), area);
+                return;
+            }
+            Some(Err(e)) => {
+                let msg = Span::styled(e.message.clone(), Style::default().fg(Color::Red));
+                f.render_widget(Paragraph::new(msg).block(block), area);
+                return;
+            }
+            Some(Ok(files)) if files.is_empty() => {
+                f.render_widget(
+                    Paragraph::new("Nothing to commit, working tree clean").block(block),
+                    area,
+                );
+                return;
+            }
+            Some(Ok(files)) => files,
+        };
+
+        // Section headers are rows too, so track where the selection lands
+        let mut rows = Vec::new();
+        let mut selected_row = 0;
+        let mut section = None;
+        for (i, file) in files.iter().enumerate() {
+            if section != Some(file.section) {
+                section = Some(file.section);
+                let count = files.iter().filter(|f| f.section == file.section).count();
+                rows.push(
+                    Row::new(vec![
+                        Cell::from(""),
+                        Cell::from(format!("{} ({count})", file.section.title())),
+                    ])
+                    .style(
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                );
+            }
+            if i == self.selected {
+                selected_row = rows.len();
+            }
+            rows.push(file_row(file));
+        }
+
+        let widths = [
+            Constraint::Length(3),
+            Constraint::Min(20),
+            Constraint::Min(20),
+        ];
+        let table = Table::new(rows, widths)
+            .block(block)
+            .style(Style::default().fg(Color::White))
+            .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        let mut state = TableState::default().with_selected(Some(selected_row));
+        f.render_stateful_widget(table, area, &mut state);
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Nav {
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(self.files().len().saturating_sub(1));
+            }
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => {
+                if let Some(file) = self.files().get(self.selected) {
+                    let mode = match file.section {
+                        FileSection::Staged => DiffMode::Staged,
+                        _ => DiffMode::Unstaged,
+                    };
+                    let view = DiffView::new(self.repo.clone(), file.path.clone(), mode);
+                    return Nav::Open(View::Diff(view));
+                }
+            }
+            KeyCode::Char('s') | KeyCode::Char('u') | KeyCode::Char(' ') | KeyCode::Char('d') => {
+                if let Some(op) = self
+                    .files()
+                    .get(self.selected)
+                    .and_then(|f| file_op(f, key.code))
+                {
+                    return Nav::Apply {
+                        repo: self.repo.clone(),
+                        op,
+                    };
+                }
+            }
+            KeyCode::Esc | KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') => {
+                return Nav::Back
+            }
+            _ => {}
+        }
+        Nav::Stay
+    }
+}
+
+/// `s` stages, `u` unstages, space toggles and `d` discards, where that
+/// makes sense for the file's section.
+fn file_op(file: &FileChange, key: KeyCode) -> Option<IndexOp> {
+    let path = file.path.clone();
+    match (key, file.section) {
+        (KeyCode::Char('u') | KeyCode::Char(' '), FileSection::Staged) => {
+            Some(IndexOp::UnstageFile(path))
+        }
+        (KeyCode::Char('s'), FileSection::Staged) => None,
+        (KeyCode::Char('s') | KeyCode::Char(' '), _) => Some(IndexOp::StageFile(path)),
+        (KeyCode::Char('d'), FileSection::Unstaged | FileSection::Untracked) => {
+            Some(IndexOp::DiscardFile(path))
+        }
+        _ => None,
+    }
+}
+
+fn file_row(file: &FileChange) -> Row<'static> {
+    let color = match file.section {
+        FileSection::Conflicted => Color::Red,
+        FileSection::Staged => Color::Green,
+        FileSection::Unstaged => Color::Red,
+        FileSection::Untracked => Color::Magenta,
+    };
+
+    let note = if let Some(kind) = file.conflict {
+        if file.markers > 0 {
+            format!("{}, {} conflict markers", kind.label(), file.markers)
+        } else {
+            kind.label().to_string()
+        }
+    } else if let Some(from) = &file.renamed_from {
+        format!("renamed from {from}")
+    } else {
+        String::new()
+    };
+
+    Row::new(vec![
+        Cell::from(format!(" {}", file.code)).style(Style::default().fg(color)),
+        Cell::from(file.path.clone()).style(Style::default().fg(color)),
+        Cell::from(note).style(Style::default().fg(Color::DarkGray)),
+    ])
+}
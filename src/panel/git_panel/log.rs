@@ -0,0 +1,605 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use git2::Oid;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState, Wrap},
+    Frame,
+};
+
+use crate::action::Action;
+use crate::source::git::branches::BranchOp;
+use crate::source::git::log::{CommitDetail, LogCommit, RefLabel};
+use crate::source::git::{GitEvent, GitRequest, RepoError};
+use crate::ui::format::format_age;
+use crate::ui::input::TextInput;
+
+use super::Nav;
+
+const PAGE_SIZE: usize = 300;
+/// Fetch the next page once the selection gets this close to the end.
+const PREFETCH_MARGIN: usize = 50;
+const LANE_COLORS: [Color; 6] = [
+    Color::Cyan,
+    Color::Green,
+    Color::Yellow,
+    Color::Magenta,
+    Color::Blue,
+    Color::Red,
+];
+
+/// Lane assignment for the commit graph. Each lane holds the commit it is
+/// waiting to reach; rows are produced one commit at a time, newest first,
+/// so pages can be appended as they arrive.
+#[derive(Default)]
+struct Graph {
+    lanes: Vec<Option<Oid>>,
+}
+
+/// Two cells per lane (symbol, then a spacer), tagged with the lane index
+/// for coloring.
+type GraphRow = Vec<(char, usize)>;
+
+impl Graph {
+    fn free_lane(&mut self) -> usize {
+        match self.lanes.iter().position(Option::is_none) {
+            Some(i) => i,
+            None => {
+                self.lanes.push(None);
+                self.lanes.len() - 1
+            }
+        }
+    }
+
+    fn row(&mut self, id: Oid, parents: &[Oid]) -> GraphRow {
+        let col = match self.lanes.iter().position(|l| *l == Some(id)) {
+            Some(col) => col,
+            None => self.free_lane(),
+        };
+        let mut cells: GraphRow = self
+            .lanes
+            .iter()
+            .enumerate()
+            .flat_map(|(i, lane)| [(if lane.is_some() { '│' } else { ' ' }, i), (' ', i)])
+            .collect();
+        cells[2 * col] = ('●', col);
+
+        // Other lanes that were heading for this commit join it here
+        for j in 0..self.lanes.len() {
+            if j != col && self.lanes[j] == Some(id) {
+                self.lanes[j] = None;
+                connect(&mut cells, col, j, if j > col { '╯' } else { '╰' });
+            }
+        }
+
+        self.lanes[col] = parents.first().copied();
+        for &parent in parents.iter().skip(1) {
+            match self.lanes.iter().position(|l| *l == Some(parent)) {
+                Some(k) => connect(&mut cells, col, k, if k > col { '┤' } else { '├' }),
+                None => {
+                    let k = self.free_lane();
+                    self.lanes[k] = Some(parent);
+                    connect(&mut cells, col, k, if k > col { '╮' } else { '╭' });
+                }
+            }
+        }
+
+        while self.lanes.last() == Some(&None) {
+            self.lanes.pop();
+        }
+        cells
+    }
+}
+
+/// Draw a horizontal link from lane `from` to lane `to`, ending in `end`.
+fn connect(cells: &mut GraphRow, from: usize, to: usize, end: char) {
+    while cells.len() < 2 * (to + 1) {
+        let lane = cells.len() / 2;
+        cells.push((' ', lane));
+    }
+    cells[2 * to] = (end, to);
+    let (lo, hi) = (from.min(to), from.max(to));
+    for cell in &mut cells[2 * lo + 1..2 * hi] {
+        let ch = match cell.0 {
+            '│' => '┼',
+            ' ' => '─',
+            other => other,
+        };
+        *cell = (ch, to);
+    }
+}
+
+/// What the text field under the log is for.
+enum Prompt {
+    Search,
+    /// Name for a new branch at this commit
+    Branch(Oid),
+}
+
+struct LogRow {
+    commit: LogCommit,
+    graph: GraphRow,
+}
+
+/// Commit history of one repo with its branch graph, loaded a page at a time.
+pub struct LogView {
+    repo: PathBuf,
+    rows: Vec<LogRow>,
+    graph: Graph,
+    /// Branch and tag names by commit, from the first page
+    labels: HashMap<Oid, Vec<RefLabel>>,
+    more: bool,
+    loading: bool,
+    error: Option<RepoError>,
+    selected: usize,
+    detail: Option<(Oid, Option<Result<CommitDetail, RepoError>>)>,
+    prompt: Option<(Prompt, TextInput)>,
+    query: Option<String>,
+    /// A search ran off the loaded commits and is waiting for the next page
+    searching: bool,
+    status: Option<String>,
+}
+
+impl LogView {
+    pub fn new(repo: PathBuf) -> Self {
+        Self {
+            repo,
+            rows: Vec::new(),
+            graph: Graph::default(),
+            labels: HashMap::new(),
+            more: false,
+            loading: true,
+            error: None,
+            selected: 0,
+            detail: None,
+            prompt: None,
+            query: None,
+            searching: false,
+            status: None,
+        }
+    }
+
+    pub fn repo(&self) -> &Path {
+        &self.repo
+    }
+
+    pub fn request(&self) -> GitRequest {
+        self.page_request(0, None)
+    }
+
+    fn page_request(&self, skip: usize, find: Option<String>) -> GitRequest {
+        GitRequest::Log {
+            repo: self.repo.clone(),
+            skip,
+            limit: PAGE_SIZE,
+            find,
+        }
+    }
+
+    pub fn captures_input(&self) -> bool {
+        self.prompt.is_some()
+    }
+
+    pub fn on_event(&mut self, event: &GitEvent) {
+        match event {
+            GitEvent::Log { repo, skip, result } if *repo == self.repo => {
+                if *skip == 0 {
+                    self.rows.clear();
+                    self.graph = Graph::default();
+                } else if *skip != self.rows.len() {
+                    return;
+                }
+                self.loading = false;
+                match result {
+                    Ok(page) => {
+                        self.error = None;
+                        self.more = page.more;
+                        if let Some(labels) = &page.labels {
+                            self.labels = labels.clone();
+                        }
+                        for commit in &page.commits {
+                            let graph = self.graph.row(commit.id, &commit.parents);
+                            self.rows.push(LogRow {
+                                commit: commit.clone(),
+                                graph,
+                            });
+                        }
+                        self.selected = self.selected.min(self.rows.len().saturating_sub(1));
+                        if self.searching {
+                            self.searching = false;
+                            self.resume_search(*skip, page.commits.len());
+                        }
+                    }
+                    Err(e) => self.error = Some(e.clone()),
+                }
+            }
+            GitEvent::CommitDetail { repo, id, result } if *repo == self.repo => {
+                if let Some((wanted, detail)) = &mut self.detail {
+                    if wanted == id {
+                        *detail = Some(result.clone());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn matches(&self, row: &LogRow) -> bool {
+        self.query
+            .as_ref()
+            .is_some_and(|q| row.commit.matches(&q.to_lowercase()))
+    }
+
+    fn find_forward(&self, from: usize) -> Option<usize> {
+        (from..self.rows.len()).find(|&i| self.matches(&self.rows[i]))
+    }
+
+    /// A page of `read` commits fetched for a search arrived: the worker read
+    /// until it hit a match or its scan limit, so anything not found here is
+    /// either the end of history or worth another search. The detail pane
+    /// closes rather than lagging behind the jump.
+    fn resume_search(&mut self, from: usize, read: usize) {
+        match self.find_forward(from) {
+            Some(i) => {
+                self.selected = i;
+                self.status = None;
+                self.detail = None;
+            }
+            None if self.more => {
+                self.status = Some(format!(
+                    "No match in the next {read} commits; search again to read on"
+                ))
+            }
+            None => self.status = Some("No more matches".to_string()),
+        }
+    }
+
+    /// Select the next match at or after `from` (or before it, backwards).
+    /// Forward searches that run out of loaded commits ask the worker to read
+    /// on until it finds one.
+    fn search_from(&mut self, from: usize, forward: bool) -> Option<Action> {
+        let found = if forward {
+            self.find_forward(from)
+        } else {
+            (0..from.min(self.rows.len()))
+                .rev()
+                .find(|&i| self.matches(&self.rows[i]))
+        };
+        match found {
+            Some(i) => {
+                self.selected = i;
+                self.status = None;
+                self.after_move()
+            }
+            None if forward && self.more => {
+                if self.loading {
+                    self.status = Some("Still loading, try again".to_string());
+                    return None;
+                }
+                self.loading = true;
+                self.searching = true;
+                self.status = Some("Searching…".to_string());
+                Some(Action::Git(
+                    self.page_request(self.rows.len(), self.query.clone()),
+                ))
+            }
+            None => {
+                self.status = Some("No more matches".to_string());
+                None
+            }
+        }
+    }
+
+    fn load_more(&mut self) -> Option<Action> {
+        if self.loading || !self.more {
+            return None;
+        }
+        self.loading = true;
+        Some(Action::Git(self.page_request(self.rows.len(), None)))
+    }
+
+    /// Keep an open detail pane on the selected commit, and keep pages
+    /// coming as the selection nears the end.
+    fn after_move(&mut self) -> Option<Action> {
+        if self.detail.is_some() {
+            if let Some(action) = self.show_detail() {
+                return Some(action);
+            }
+        }
+        if self.selected + PREFETCH_MARGIN >= self.rows.len() {
+            return self.load_more();
+        }
+        None
+    }
+
+    fn show_detail(&mut self) -> Option<Action> {
+        let id = self.rows.get(self.selected)?.commit.id;
+        if self.detail.as_ref().is_some_and(|(shown, _)| *shown == id) {
+            return None;
+        }
+        self.detail = Some((id, None));
+        Some(Action::Git(GitRequest::CommitDetail {
+            repo: self.repo.clone(),
+            id,
+        }))
+    }
+
+    fn move_selection(&mut self, delta: isize) -> Option<Action> {
+        self.selected = self
+            .selected
+            .saturating_add_signed(delta)
+            .min(self.rows.len().saturating_sub(1));
+        self.after_move()
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Nav {
+        if let Some((_, input)) = &mut self.prompt {
+            match key.code {
+                KeyCode::Esc => self.prompt = None,
+                KeyCode::Enter => {
+                    let value = input.value().trim().to_string();
+                    let prompt = self.prompt.take().map(|(p, _)| p);
+                    if value.is_empty() {
+                        return Nav::Stay;
+                    }
+                    return match prompt {
+                        Some(Prompt::Branch(from)) => Nav::Branch {
+                            repo: self.repo.clone(),
+                            op: BranchOp::Create { name: value, from },
+                        },
+                        _ => {
+                            self.query = Some(value);
+                            nav(self.search_from(self.selected + 1, true))
+                        }
+                    };
+                }
+                _ => {
+                    input.handle_key(key);
+                }
+            }
+            return Nav::Stay;
+        }
+
+        let action = match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::PageUp => self.move_selection(-20),
+            KeyCode::PageDown => self.move_selection(20),
+            KeyCode::Home | KeyCode::Char('g') => self.move_selection(isize::MIN),
+            KeyCode::End | KeyCode::Char('G') => self.move_selection(isize::MAX),
+            KeyCode::Enter => {
+                if self.detail.take().is_none() {
+                    self.show_detail()
+                } else {
+                    None
+                }
+            }
+            KeyCode::Char('/') => {
+                let input = TextInput::new(self.query.as_deref().unwrap_or_default());
+                self.prompt = Some((Prompt::Search, input));
+                None
+            }
+            KeyCode::Char('b') => {
+                if let Some(row) = self.rows.get(self.selected) {
+                    self.prompt = Some((Prompt::Branch(row.commit.id), TextInput::default()));
+                }
+                None
+            }
+            KeyCode::Char('n') => self.search_from(self.selected + 1, true),
+            KeyCode::Char('N') => self.search_from(self.selected, false),
+            KeyCode::Esc | KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') => {
+                if self.detail.take().is_some() {
+                    None
+                } else {
+                    return Nav::Back;
+                }
+            }
+            _ => None,
+        };
+        nav(action)
+    }
+
+    pub fn draw(&self, f: &mut Frame, area: Rect) {
+        let mut constraints = vec![Constraint::Min(5)];
+        if self.detail.is_some() {
+            constraints.push(Constraint::Percentage(45));
+        }
+        if self.prompt.is_some() {
+            constraints.push(Constraint::Length(3));
+        }
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(area);
+
+        self.draw_log(f, chunks[0]);
+        let mut next = 1;
+        if let Some((_, detail)) = &self.detail {
+            draw_detail(f, chunks[next], detail.as_ref());
+            next += 1;
+        }
+        match &self.prompt {
+            Some((Prompt::Search, input)) => input.draw(
+                f,
+                chunks[next],
+                "Search subjects and authors (Enter: find, Esc: cancel)",
+            ),
+            Some((Prompt::Branch(from), input)) => {
+                let title = format!(
+                    "New branch at {} (Enter: create, Esc: cancel)",
+                    &from.to_string()[..7]
+                );
+                input.draw(f, chunks[next], &title);
+            }
+            None => {}
+        }
+    }
+
+    fn draw_log(&self, f: &mut Frame, area: Rect) {
+        let mut title = format!(" Log — {} ", self.repo.display());
+        if self.loading {
+            title.push_str("(loading…) ");
+        }
+        if let Some(status) = &self.status {
+            title.push_str(&format!("({status}) "));
+        }
+        let block = Block::default().borders(Borders::ALL).title(title);
+
+        if let Some(e) = &self.error {
+            let msg = Span::styled(e.message.clone(), Style::default().fg(Color::Red));
+            f.render_widget(Paragraph::new(msg).block(block), area);
+            return;
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        let graph_width = self.rows.iter().map(|r| r.graph.len()).max().unwrap_or(0);
+        let rows: Vec<Row> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let c = &row.commit;
+                let graph: Vec<Span> = row
+                    .graph
+                    .iter()
+                    .map(|&(ch, lane)| {
+                        Span::styled(
+                            ch.to_string(),
+                            Style::default().fg(LANE_COLORS[lane % LANE_COLORS.len()]),
+                        )
+                    })
+                    .collect();
+
+                let mut subject: Vec<Span> = self
+                    .labels
+                    .get(&c.id)
+                    .map_or(&[][..], Vec::as_slice)
+                    .iter()
+                    .map(ref_span)
+                    .collect();
+                let style = if self.matches(row) {
+                    Style::default().fg(Color::Yellow)
+                } else {
+                    Style::default()
+                };
+                subject.push(Span::styled(c.summary.clone(), style));
+
+                Row::new(vec![
+                    Cell::from(Line::from(graph)),
+                    Cell::from(c.id.to_string()[..7].to_string())
+                        .style(Style::default().fg(Color::Yellow)),
+                    Cell::from(c.author.clone()).style(Style::default().fg(Color::Cyan)),
+                    Cell::from(format_age((now - c.time).max(0) as u64))
+                        .style(Style::default().fg(Color::DarkGray)),
+                    Cell::from(Line::from(subject)),
+                ])
+            })
+            .collect();
+
+        let widths = [
+            Constraint::Length(graph_width as u16),
+            Constraint::Length(7),
+            Constraint::Length(16),
+            Constraint::Length(15),
+            Constraint::Min(20),
+        ];
+        let table = Table::new(rows, widths)
+            .block(block)
+            .style(Style::default().fg(Color::White))
+            .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        let mut state = TableState::default().with_selected(Some(self.selected));
+        f.render_stateful_widget(table, area, &mut state);
+    }
+}
+
+fn nav(action: Option<Action>) -> Nav {
+    action.map_or(Nav::Stay, Nav::Run)
+}
+
+fn ref_span(label: &RefLabel) -> Span<'static> {
+    let style = match label {
+        RefLabel::Head(_) => Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD),
+        RefLabel::Branch(_) => Style::default().fg(Color::Green),
+        RefLabel::Remote(_) => Style::default().fg(Color::Red),
+        RefLabel::Tag(_) => Style::default().fg(Color::Yellow),
+    };
+    let text = match label {
+        RefLabel::Tag(name) => format!("(tag: {name}) "),
+        other => format!("({}) ", other.name()),
+    };
+    Span::styled(text, style)
+}
+
+fn draw_detail(f: &mut Frame, area: Rect, detail: Option<&Result<CommitDetail, RepoError>>) {
+    let block = Block::default().borders(Borders::ALL).title(" Commit ");
+    let detail = match detail {
+        None => {
+            f.render_widget(Paragraph::new("Loading…").block(block), area);
+            return;
+        }
+        Some(Err(e)) => {
+            let msg = Span::styled(e.message.clone(), Style::default().fg(Color::Red));
+            f.render_widget(Paragraph::new(msg).block(block), area);
+            return;
+        }
+        Some(Ok(detail)) => detail,
+    };
+
+    let label = |text: &'static str| Span::styled(text, Style::default().fg(Color::Yellow));
+    let date = chrono::DateTime::from_timestamp(detail.time, 0)
+        .map(|d| {
+            d.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_default();
+    let mut lines = vec![
+        Line::from(vec![label("commit    "), Span::raw(detail.id.to_string())]),
+        Line::from(vec![label("Author:   "), Span::raw(detail.author.clone())]),
+        Line::from(vec![
+            label("Committer:"),
+            Span::raw(format!(" {}", detail.committer)),
+        ]),
+        Line::from(vec![label("Date:     "), Span::raw(date)]),
+        Line::from(""),
+    ];
+    lines.extend(
+        detail
+            .message
+            .lines()
+            .map(|l| Line::from(format!("    {l}"))),
+    );
+    lines.push(Line::from(""));
+    for file in &detail.files {
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("+{:<5}", file.insertions),
+                Style::default().fg(Color::Green),
+            ),
+            Span::styled(
+                format!("-{:<5}", file.deletions),
+                Style::default().fg(Color::Red),
+            ),
+            Span::raw(file.path.clone()),
+        ]));
+    }
+    lines.push(Line::from(format!(
+        "{} files changed, {} insertions(+), {} deletions(-)",
+        detail.files.len(),
+        detail.insertions,
+        detail.deletions
+    )));
+
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+}
@@ -0,0 +1,330 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+use crate::source::git::stale::{BranchRef, Deleted, StaleBranch, StaleReport};
+use crate::source::git::{GitEvent, GitRequest};
+use crate::ui::format::format_age;
+
+use super::Nav;
+
+/// Lines of restore commands shown at once; PageUp/PageDown scroll the rest.
+const DELETED_HEIGHT: usize = 10;
+
+/// Stale local branches across every repo, for bulk cleanup.
+pub struct StaleView {
+    repos: Vec<PathBuf>,
+    report: Option<StaleReport>,
+    selected: usize,
+    marked: HashSet<BranchRef>,
+    /// Waiting for `y` to delete these
+    confirm: Option<Vec<BranchRef>>,
+    /// Results of every delete since the view opened, oldest first, kept on
+    /// screen for recovery
+    deleted: Vec<Deleted>,
+    /// First line of `deleted` shown
+    scroll: usize,
+}
+
+impl StaleView {
+    pub fn new(repos: Vec<PathBuf>) -> Self {
+        Self {
+            repos,
+            report: None,
+            selected: 0,
+            marked: HashSet::new(),
+            confirm: None,
+            deleted: Vec::new(),
+            scroll: 0,
+        }
+    }
+
+    pub fn captures_input(&self) -> bool {
+        self.confirm.is_some()
+    }
+
+    pub fn request(&self) -> GitRequest {
+        GitRequest::StaleBranches(self.repos.clone())
+    }
+
+    fn branches(&self) -> &[StaleBranch] {
+        match &self.report {
+            Some(report) => &report.branches,
+            None => &[],
+        }
+    }
+
+    pub fn on_event(&mut self, event: &GitEvent) {
+        match event {
+            GitEvent::StaleBranches(report) => {
+                let listed: HashSet<BranchRef> =
+                    report.branches.iter().map(StaleBranch::key).collect();
+                self.marked.retain(|b| listed.contains(b));
+                self.report = Some(report.clone());
+                self.selected = self.selected.min(self.branches().len().saturating_sub(1));
+            }
+            GitEvent::DeletedBranches { deleted, .. } => {
+                // Start at the newest batch
+                let newest = self.deleted.len();
+                self.deleted.extend(deleted.iter().cloned());
+                self.scroll = newest.min(self.last_page());
+            }
+            _ => {}
+        }
+    }
+
+    /// Scroll position that shows the last page of `deleted`.
+    fn last_page(&self) -> usize {
+        self.deleted.len().saturating_sub(DELETED_HEIGHT)
+    }
+
+    /// Marked branches, else the selected one, in report order.
+    fn targets(&self) -> Vec<BranchRef> {
+        let branches = self.branches().iter().map(StaleBranch::key);
+        if self.marked.is_empty() {
+            branches.skip(self.selected).take(1).collect()
+        } else {
+            branches.filter(|b| self.marked.contains(b)).collect()
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Nav {
+        if let Some(branches) = self.confirm.take() {
+            if key.code == KeyCode::Char('y') {
+                return Nav::DeleteBranches(branches);
+            }
+            return Nav::Stay;
+        }
+
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(self.branches().len().saturating_sub(1));
+            }
+            KeyCode::Char(' ') => {
+                if let Some(branch) = self.branches().get(self.selected) {
+                    let key = branch.key();
+                    if !self.marked.remove(&key) {
+                        self.marked.insert(key);
+                    }
+                    self.selected =
+                        (self.selected + 1).min(self.branches().len().saturating_sub(1));
+                }
+            }
+            KeyCode::Char('a') => {
+                if self.marked.len() == self.branches().len() {
+                    self.marked.clear();
+                } else {
+                    self.marked = self.branches().iter().map(StaleBranch::key).collect();
+                }
+            }
+            KeyCode::PageUp => {
+                self.scroll = self.scroll.saturating_sub(DELETED_HEIGHT);
+            }
+            KeyCode::PageDown => {
+                self.scroll = (self.scroll + DELETED_HEIGHT).min(self.last_page());
+            }
+            KeyCode::Char('d') | KeyCode::Char('D') => {
+                let targets = self.targets();
+                if !targets.is_empty() {
+                    self.confirm = Some(targets);
+                }
+            }
+            KeyCode::Esc | KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') => {
+                return Nav::Back
+            }
+            _ => {}
+        }
+        Nav::Stay
+    }
+
+    pub fn draw(&self, f: &mut Frame, area: Rect) {
+        let mut constraints = vec![Constraint::Min(5)];
+        if self.confirm.is_some() {
+            constraints.push(Constraint::Length(3));
+        } else if !self.deleted.is_empty() {
+            let height = self.deleted.len().min(DELETED_HEIGHT) as u16 + 2;
+            constraints.push(Constraint::Length(height));
+        }
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(area);
+
+        self.draw_table(f, chunks[0]);
+        if let Some(branches) = &self.confirm {
+            draw_confirm(f, chunks[1], branches);
+        } else if !self.deleted.is_empty() {
+            draw_deleted(f, chunks[1], &self.deleted, self.scroll);
+        }
+    }
+
+    fn draw_table(&self, f: &mut Frame, area: Rect) {
+        let mut title = format!(" Stale branches — {} repos ", self.repos.len());
+        if !self.marked.is_empty() {
+            title.push_str(&format!("({} marked) ", self.marked.len()));
+        }
+        let block = Block::default().borders(Borders::ALL).title(title);
+
+        let Some(report) = &self.report else {
+            f.render_widget(Paragraph::new("Reading branches…").block(block), area);
+            return;
+        };
+
+        let mut lines: Vec<Line> = report
+            .failed
+            .iter()
+            .map(|(name, e)| {
+                Line::from(Span::styled(
+                    format!("{name}: {}", e.message),
+                    Style::default().fg(Color::Red),
+                ))
+            })
+            .collect();
+        if report.branches.is_empty() {
+            lines.insert(0, Line::from("No stale branches"));
+            f.render_widget(Paragraph::new(lines).block(block), area);
+            return;
+        }
+        let area = if lines.is_empty() {
+            area
+        } else {
+            // Repos that couldn't be read are listed under the table
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(3), Constraint::Length(lines.len() as u16)])
+                .split(area);
+            f.render_widget(Paragraph::new(lines), chunks[1]);
+            chunks[0]
+        };
+
+        let header = Row::new(
+            [
+                "",
+                "Repo",
+                "Branch",
+                "Why",
+                "Last commit",
+                "Author",
+                "Commit",
+                "Subject",
+            ]
+            .iter()
+            .map(|h| {
+                Cell::from(*h).style(
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )
+            }),
+        );
+
+        let now = chrono::Utc::now().timestamp();
+        let rows: Vec<Row> = report
+            .branches
+            .iter()
+            .map(|b| stale_row(b, self.marked.contains(&b.key()), now))
+            .collect();
+        let widths = [
+            Constraint::Length(1),
+            Constraint::Min(12),
+            Constraint::Min(15),
+            Constraint::Length(18),
+            Constraint::Length(15),
+            Constraint::Length(16),
+            Constraint::Length(8),
+            Constraint::Min(20),
+        ];
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(block)
+            .style(Style::default().fg(Color::White))
+            .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        let mut state = TableState::default().with_selected(Some(self.selected));
+        f.render_stateful_widget(table, area, &mut state);
+    }
+}
+
+fn stale_row(branch: &StaleBranch, marked: bool, now: i64) -> Row<'static> {
+    let why_style = if branch.merged {
+        Style::default().fg(Color::Green)
+    } else {
+        Style::default().fg(Color::Red)
+    };
+    Row::new(vec![
+        Cell::from(if marked { "●" } else { "" }).style(Style::default().fg(Color::Yellow)),
+        Cell::from(branch.repo_name.clone()),
+        Cell::from(branch.name.clone()).style(Style::default().fg(Color::Cyan)),
+        Cell::from(branch.reasons()).style(why_style),
+        Cell::from(format_age((now - branch.time).max(0) as u64))
+            .style(Style::default().fg(Color::DarkGray)),
+        Cell::from(branch.author.clone()),
+        Cell::from(branch.target.to_string()[..7].to_string())
+            .style(Style::default().fg(Color::Yellow)),
+        Cell::from(branch.summary.clone()),
+    ])
+}
+
+fn draw_confirm(f: &mut Frame, area: Rect, branches: &[BranchRef]) {
+    let repos: HashSet<&PathBuf> = branches.iter().map(|b| &b.repo).collect();
+    let question = match branches {
+        [one] => format!("Delete {}?", one.name),
+        _ => format!(
+            "Delete {} branches in {} repo(s)?",
+            branches.len(),
+            repos.len()
+        ),
+    };
+    let text = Line::from(vec![
+        Span::raw(format!(
+            "{question} Their commits are listed afterwards for recovery.  "
+        )),
+        Span::styled("y", Style::default().fg(Color::Yellow)),
+        Span::raw(": yes  "),
+        Span::styled("any other key", Style::default().fg(Color::Yellow)),
+        Span::raw(": cancel"),
+    ]);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Red))
+        .title(" Confirm ");
+    f.render_widget(Paragraph::new(text).block(block), area);
+}
+
+/// One line per branch: how to recreate it, or why it was kept.
+fn draw_deleted(f: &mut Frame, area: Rect, deleted: &[Deleted], scroll: usize) {
+    let lines: Vec<Line> = deleted
+        .iter()
+        .map(|d| match &d.result {
+            Ok(()) => Line::from(d.restore_command()),
+            Err(e) => Line::from(Span::styled(
+                format!("kept {}: {}", d.branch.name, e.message),
+                Style::default().fg(Color::Red),
+            )),
+        })
+        .collect();
+    let count = deleted.iter().filter(|d| d.result.is_ok()).count();
+    let title = if deleted.len() > DELETED_HEIGHT {
+        format!(" Deleted {count} branch(es) — to restore one (PgUp/PgDn to scroll): ")
+    } else {
+        format!(" Deleted {count} branch(es) — to restore one: ")
+    };
+    let block = Block::default().borders(Borders::ALL).title(title);
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .scroll((scroll as u16, 0)),
+        area,
+    );
+}
@@ -0,0 +1,311 @@
+use std::path::{Path, PathBuf};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use git2::Oid;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+use crate::action::Action;
+use crate::source::git::stash::{StashEntry, StashOp, StashPreview, StashRef};
+use crate::source::git::{GitEvent, GitRequest, RepoError};
+use crate::ui::format::format_age;
+
+use super::Nav;
+
+/// Stash entries of one repo, with an optional preview of the selected one.
+pub struct StashView {
+    repo: PathBuf,
+    stashes: Option<Result<Vec<StashEntry>, RepoError>>,
+    selected: usize,
+    preview: Option<(Oid, Option<Result<StashPreview, RepoError>>)>,
+    scroll: usize,
+    /// Waiting for `y` to drop this entry
+    confirm_drop: Option<StashRef>,
+}
+
+impl StashView {
+    pub fn new(repo: PathBuf) -> Self {
+        Self {
+            repo,
+            stashes: None,
+            selected: 0,
+            preview: None,
+            scroll: 0,
+            confirm_drop: None,
+        }
+    }
+
+    pub fn repo(&self) -> &Path {
+        &self.repo
+    }
+
+    pub fn request(&self) -> GitRequest {
+        GitRequest::Stashes(self.repo.clone())
+    }
+
+    pub fn captures_input(&self) -> bool {
+        self.confirm_drop.is_some()
+    }
+
+    fn stashes(&self) -> &[StashEntry] {
+        match &self.stashes {
+            Some(Ok(stashes)) => stashes,
+            _ => &[],
+        }
+    }
+
+    pub fn on_event(&mut self, event: &GitEvent) {
+        match event {
+            GitEvent::Stashes { repo, result } if *repo == self.repo => {
+                self.stashes = Some(result.clone());
+                self.selected = self.selected.min(self.stashes().len().saturating_sub(1));
+                // The entry under the preview may be gone after a pop or drop
+                let shown = self.preview.as_ref().map(|(id, _)| *id);
+                if shown.is_some() && self.stashes().get(self.selected).map(|s| s.id) != shown {
+                    self.preview = None;
+                }
+            }
+            GitEvent::StashPreview { repo, id, result } if *repo == self.repo => {
+                if let Some((wanted, preview)) = &mut self.preview {
+                    if wanted == id {
+                        *preview = Some(result.clone());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn show_preview(&mut self) -> Option<Action> {
+        let id = self.stashes().get(self.selected)?.id;
+        if self.preview.as_ref().is_some_and(|(shown, _)| *shown == id) {
+            return None;
+        }
+        self.preview = Some((id, None));
+        self.scroll = 0;
+        Some(Action::Git(GitRequest::StashPreview {
+            repo: self.repo.clone(),
+            id,
+        }))
+    }
+
+    fn move_selection(&mut self, delta: isize) -> Option<Action> {
+        self.selected = self
+            .selected
+            .saturating_add_signed(delta)
+            .min(self.stashes().len().saturating_sub(1));
+        if self.preview.is_some() {
+            return self.show_preview();
+        }
+        None
+    }
+
+    fn op(&self, op: StashOp) -> Nav {
+        Nav::Stash {
+            repo: self.repo.clone(),
+            op,
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Nav {
+        if let Some(stash) = self.confirm_drop.take() {
+            if key.code == KeyCode::Char('y') {
+                return self.op(StashOp::Drop(stash));
+            }
+            return Nav::Stay;
+        }
+
+        let stash = self.stashes().get(self.selected).map(StashEntry::key);
+        let action = match (key.code, stash) {
+            (KeyCode::Up | KeyCode::Char('k'), _) => self.move_selection(-1),
+            (KeyCode::Down | KeyCode::Char('j'), _) => self.move_selection(1),
+            (KeyCode::PageUp, _) => {
+                self.scroll = self.scroll.saturating_sub(20);
+                None
+            }
+            (KeyCode::PageDown, _) => {
+                self.scroll += 20;
+                None
+            }
+            (KeyCode::Enter, _) => {
+                if self.preview.take().is_none() {
+                    self.show_preview()
+                } else {
+                    None
+                }
+            }
+            (KeyCode::Char('a'), Some(stash)) => return self.op(StashOp::Apply(stash)),
+            (KeyCode::Char('p'), Some(stash)) => return self.op(StashOp::Pop(stash)),
+            (KeyCode::Char('d'), Some(stash)) => {
+                self.confirm_drop = Some(stash);
+                None
+            }
+            (KeyCode::Esc | KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h'), _) => {
+                if self.preview.take().is_some() {
+                    None
+                } else {
+                    return Nav::Back;
+                }
+            }
+            _ => None,
+        };
+        action.map_or(Nav::Stay, Nav::Run)
+    }
+
+    pub fn draw(&self, f: &mut Frame, area: Rect) {
+        let mut constraints = vec![Constraint::Min(5)];
+        if self.preview.is_some() {
+            constraints[0] = Constraint::Length(10);
+            constraints.push(Constraint::Min(5));
+        }
+        if self.confirm_drop.is_some() {
+            constraints.push(Constraint::Length(3));
+        }
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(area);
+
+        self.draw_list(f, chunks[0]);
+        let mut next = 1;
+        if let Some((_, preview)) = &self.preview {
+            draw_preview(f, chunks[next], preview.as_ref(), self.scroll);
+            next += 1;
+        }
+        if let Some(stash) = self.confirm_drop {
+            let text = Line::from(vec![
+                Span::raw(format!(
+                    "Drop stash@{{{}}}? This cannot be undone.  ",
+                    stash.index
+                )),
+                Span::styled("y", Style::default().fg(Color::Yellow)),
+                Span::raw(": drop  "),
+                Span::styled("any other key", Style::default().fg(Color::Yellow)),
+                Span::raw(": cancel"),
+            ]);
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Red))
+                .title(" Confirm ");
+            f.render_widget(Paragraph::new(text).block(block), chunks[next]);
+        }
+    }
+
+    fn draw_list(&self, f: &mut Frame, area: Rect) {
+        let title = format!(" Stashes — {} ", self.repo.display());
+        let block = Block::default().borders(Borders::ALL).title(title);
+
+        let stashes = match &self.stashes {
+            None => {
+                f.render_widget(Paragraph::new("Loading…").block(block), area);
+                return;
+            }
+            Some(Err(e)) => {
+                let msg = Span::styled(e.message.clone(), Style::default().fg(Color::Red));
+                f.render_widget(Paragraph::new(msg).block(block), area);
+                return;
+            }
+            Some(Ok(stashes)) if stashes.is_empty() => {
+                f.render_widget(Paragraph::new("No stashes").block(block), area);
+                return;
+            }
+            Some(Ok(stashes)) => stashes,
+        };
+
+        let now = chrono::Utc::now().timestamp();
+        let rows: Vec<Row> = stashes
+            .iter()
+            .map(|s| {
+                Row::new(vec![
+                    Cell::from(format!("stash@{{{}}}", s.index))
+                        .style(Style::default().fg(Color::Yellow)),
+                    Cell::from(format_age((now - s.time).max(0) as u64))
+                        .style(Style::default().fg(Color::DarkGray)),
+                    Cell::from(s.message.clone()),
+                ])
+            })
+            .collect();
+
+        let widths = [
+            Constraint::Length(10),
+            Constraint::Length(15),
+            Constraint::Min(20),
+        ];
+        let table = Table::new(rows, widths)
+            .block(block)
+            .style(Style::default().fg(Color::White))
+            .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        let mut state = TableState::default().with_selected(Some(self.selected));
+        f.render_stateful_widget(table, area, &mut state);
+    }
+}
+
+fn draw_preview(
+    f: &mut Frame,
+    area: Rect,
+    preview: Option<&Result<StashPreview, RepoError>>,
+    scroll: usize,
+) {
+    let block = Block::default().borders(Borders::ALL).title(" Preview ");
+    let preview = match preview {
+        None => {
+            f.render_widget(Paragraph::new("Loading…").block(block), area);
+            return;
+        }
+        Some(Err(e)) => {
+            let msg = Span::styled(e.message.clone(), Style::default().fg(Color::Red));
+            f.render_widget(Paragraph::new(msg).block(block), area);
+            return;
+        }
+        Some(Ok(preview)) => preview,
+    };
+
+    let mut lines: Vec<Line> = preview
+        .files
+        .iter()
+        .map(|file| {
+            Line::from(vec![
+                Span::styled(
+                    format!("+{:<5}", file.insertions),
+                    Style::default().fg(Color::Green),
+                ),
+                Span::styled(
+                    format!("-{:<5}", file.deletions),
+                    Style::default().fg(Color::Red),
+                ),
+                Span::raw(file.path.clone()),
+            ])
+        })
+        .collect();
+    lines.push(Line::from(""));
+    lines.extend(preview.lines.iter().map(|(origin, text)| {
+        let (prefix, style) = match origin {
+            '+' => ("+", Style::default().fg(Color::Green)),
+            '-' => ("-", Style::default().fg(Color::Red)),
+            'H' => ("", Style::default().fg(Color::Cyan)),
+            'F' => ("", Style::default().add_modifier(Modifier::BOLD)),
+            _ => (" ", Style::default()),
+        };
+        Line::from(Span::styled(format!("{prefix}{text}"), style))
+    }));
+    if preview.truncated {
+        lines.push(Line::from(Span::styled(
+            "… preview truncated",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    let scroll = scroll.min(lines.len().saturating_sub(1));
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .scroll((scroll as u16, 0)),
+        area,
+    );
+}
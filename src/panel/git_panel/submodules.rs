@@ -0,0 +1,181 @@
+use std::path::{Path, PathBuf};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style},
+    text::Span,
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+use crate::source::git::submodules::{SubmoduleInfo, SubmoduleOp, SubmoduleState};
+use crate::source::git::{GitEvent, GitRequest, RepoError};
+
+use super::Nav;
+
+/// Submodules of one repo.
+pub struct SubmodulesView {
+    repo: PathBuf,
+    submodules: Option<Result<Vec<SubmoduleInfo>, RepoError>>,
+    selected: usize,
+}
+
+impl SubmodulesView {
+    pub fn new(repo: PathBuf) -> Self {
+        Self {
+            repo,
+            submodules: None,
+            selected: 0,
+        }
+    }
+
+    pub fn repo(&self) -> &Path {
+        &self.repo
+    }
+
+    pub fn request(&self) -> GitRequest {
+        GitRequest::Submodules(self.repo.clone())
+    }
+
+    fn submodules(&self) -> &[SubmoduleInfo] {
+        match &self.submodules {
+            Some(Ok(submodules)) => submodules,
+            _ => &[],
+        }
+    }
+
+    pub fn on_event(&mut self, event: &GitEvent) {
+        match event {
+            GitEvent::Submodules { repo, result } if *repo == self.repo => {
+                self.submodules = Some(result.clone());
+                self.selected = self.selected.min(self.submodules().len().saturating_sub(1));
+            }
+            _ => {}
+        }
+    }
+
+    fn op(&self, op: SubmoduleOp) -> Nav {
+        Nav::Submodule {
+            repo: self.repo.clone(),
+            op,
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Nav {
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(self.submodules().len().saturating_sub(1));
+            }
+            KeyCode::Char('u') => {
+                if let Some(submodule) = self.submodules().get(self.selected) {
+                    return self.op(SubmoduleOp::Update {
+                        name: submodule.name.clone(),
+                    });
+                }
+            }
+            KeyCode::Char('U') if !self.submodules().is_empty() => {
+                return self.op(SubmoduleOp::UpdateAll)
+            }
+            KeyCode::Esc | KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') => {
+                return Nav::Back
+            }
+            _ => {}
+        }
+        Nav::Stay
+    }
+
+    pub fn draw(&self, f: &mut Frame, area: Rect) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" Submodules — {} ", self.repo.display()));
+
+        let submodules = match &self.submodules {
+            None => {
+                f.render_widget(Paragraph::new("Loading…").block(block), area);
+                return;
+            }
+            Some(Err(e)) => {
+                let msg = Span::styled(e.message.clone(), Style::default().fg(Color::Red));
+                f.render_widget(Paragraph::new(msg).block(block), area);
+                return;
+            }
+            Some(Ok(submodules)) if submodules.is_empty() => {
+                f.render_widget(Paragraph::new("No submodules").block(block), area);
+                return;
+            }
+            Some(Ok(submodules)) => submodules,
+        };
+
+        let header = Row::new(
+            [
+                "Path",
+                "Recorded",
+                "Checked out",
+                "State",
+                "Modified",
+                "Staged",
+                "Untracked",
+            ]
+            .iter()
+            .map(|h| {
+                Cell::from(*h).style(
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )
+            }),
+        );
+        let rows: Vec<Row> = submodules.iter().map(submodule_row).collect();
+        let widths = [
+            Constraint::Min(20),
+            Constraint::Length(10),
+            Constraint::Length(12),
+            Constraint::Length(15),
+            Constraint::Length(10),
+            Constraint::Length(8),
+            Constraint::Length(11),
+        ];
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(block)
+            .style(Style::default().fg(Color::White))
+            .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        let mut state = TableState::default().with_selected(Some(self.selected));
+        f.render_stateful_widget(table, area, &mut state);
+    }
+}
+
+pub fn submodule_state(submodule: &SubmoduleInfo) -> Cell<'static> {
+    let color = match submodule.state {
+        SubmoduleState::Uninitialized => Color::DarkGray,
+        SubmoduleState::OutOfDate => Color::Red,
+        SubmoduleState::Modified => Color::Yellow,
+        SubmoduleState::Clean => Color::Green,
+    };
+    Cell::from(submodule.state.label()).style(Style::default().fg(color))
+}
+
+/// Short id of the checked-out commit, `-` before the first update.
+pub fn checked_out(submodule: &SubmoduleInfo) -> Cell<'static> {
+    match &submodule.checked_out {
+        Some(id) => Cell::from(id.clone()).style(Style::default().fg(Color::Cyan)),
+        None => Cell::from("-").style(Style::default().fg(Color::DarkGray)),
+    }
+}
+
+fn submodule_row(submodule: &SubmoduleInfo) -> Row<'static> {
+    Row::new(vec![
+        Cell::from(submodule.path.clone()),
+        Cell::from(submodule.recorded.clone().unwrap_or("-".to_string())),
+        checked_out(submodule),
+        submodule_state(submodule),
+        Cell::from(submodule.modified.to_string()),
+        Cell::from(submodule.staged.to_string()),
+        Cell::from(submodule.untracked.to_string()),
+    ])
+}
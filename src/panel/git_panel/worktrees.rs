@@ -0,0 +1,272 @@
+use std::path::{Component, Path, PathBuf};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::Span,
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+use crate::source::git::worktrees::{WorktreeInfo, WorktreeOp};
+use crate::source::git::{GitEvent, GitRequest, RepoError};
+use crate::ui::input::TextInput;
+
+use super::Nav;
+
+/// Linked worktrees of one repo.
+pub struct WorktreesView {
+    repo: PathBuf,
+    worktrees: Option<Result<Vec<WorktreeInfo>, RepoError>>,
+    selected: usize,
+    /// Path for a new worktree
+    prompt: Option<TextInput>,
+    status: Option<String>,
+}
+
+impl WorktreesView {
+    pub fn new(repo: PathBuf) -> Self {
+        Self {
+            repo,
+            worktrees: None,
+            selected: 0,
+            prompt: None,
+            status: None,
+        }
+    }
+
+    pub fn repo(&self) -> &Path {
+        &self.repo
+    }
+
+    pub fn request(&self) -> GitRequest {
+        GitRequest::Worktrees(self.repo.clone())
+    }
+
+    pub fn captures_input(&self) -> bool {
+        self.prompt.is_some()
+    }
+
+    fn worktrees(&self) -> &[WorktreeInfo] {
+        match &self.worktrees {
+            Some(Ok(worktrees)) => worktrees,
+            _ => &[],
+        }
+    }
+
+    pub fn on_event(&mut self, event: &GitEvent) {
+        match event {
+            GitEvent::Worktrees { repo, result } if *repo == self.repo => {
+                self.worktrees = Some(result.clone());
+                self.selected = self.selected.min(self.worktrees().len().saturating_sub(1));
+            }
+            _ => {}
+        }
+    }
+
+    fn op(&self, op: WorktreeOp) -> Nav {
+        Nav::Worktree {
+            repo: self.repo.clone(),
+            op,
+        }
+    }
+
+    fn prompt_key(&mut self, mut input: TextInput, key: KeyEvent) -> Nav {
+        match key.code {
+            KeyCode::Esc => {}
+            KeyCode::Enter => {
+                let path = input.value().trim();
+                if !path.is_empty() {
+                    let path = resolve(&self.repo, Path::new(path));
+                    return self.op(WorktreeOp::Add { path });
+                }
+            }
+            _ => {
+                input.handle_key(key);
+                self.prompt = Some(input);
+            }
+        }
+        Nav::Stay
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Nav {
+        if let Some(input) = self.prompt.take() {
+            return self.prompt_key(input, key);
+        }
+        self.status = None;
+
+        let selected = self.worktrees().get(self.selected).cloned();
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(self.worktrees().len().saturating_sub(1));
+            }
+            KeyCode::Char('n') => {
+                let name = self
+                    .repo
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                self.prompt = Some(TextInput::new(&format!("../{name}-")));
+            }
+            KeyCode::Char('d') => match selected {
+                Some(worktree) if worktree.locked.is_some() => {
+                    self.status = Some(format!("{} is locked", worktree.name));
+                }
+                Some(worktree) if !worktree.prunable => {
+                    self.status = Some(format!(
+                        "{} is still in use; only worktrees whose directory is gone can be pruned",
+                        worktree.name
+                    ));
+                }
+                Some(worktree) => {
+                    return self.op(WorktreeOp::Prune {
+                        name: worktree.name,
+                    })
+                }
+                None => {}
+            },
+            KeyCode::Esc | KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') => {
+                return Nav::Back
+            }
+            _ => {}
+        }
+        Nav::Stay
+    }
+
+    pub fn draw(&self, f: &mut Frame, area: Rect) {
+        let mut constraints = vec![Constraint::Min(5)];
+        if self.prompt.is_some() {
+            constraints.push(Constraint::Length(3));
+        }
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(area);
+
+        self.draw_table(f, chunks[0]);
+        if let Some(input) = &self.prompt {
+            input.draw(
+                f,
+                chunks[1],
+                "New worktree path, relative to the repo; checks out a branch named after it (Enter: add, Esc: cancel)",
+            );
+        }
+    }
+
+    fn draw_table(&self, f: &mut Frame, area: Rect) {
+        let mut title = format!(" Worktrees — {} ", self.repo.display());
+        if let Some(status) = &self.status {
+            title.push_str(&format!("({status}) "));
+        }
+        let block = Block::default().borders(Borders::ALL).title(title);
+
+        let worktrees = match &self.worktrees {
+            None => {
+                f.render_widget(Paragraph::new("Loading…").block(block), area);
+                return;
+            }
+            Some(Err(e)) => {
+                let msg = Span::styled(e.message.clone(), Style::default().fg(Color::Red));
+                f.render_widget(Paragraph::new(msg).block(block), area);
+                return;
+            }
+            Some(Ok(worktrees)) if worktrees.is_empty() => {
+                f.render_widget(
+                    Paragraph::new("No linked worktrees; n: add one").block(block),
+                    area,
+                );
+                return;
+            }
+            Some(Ok(worktrees)) => worktrees,
+        };
+
+        let header = Row::new(
+            [
+                "Name",
+                "Branch",
+                "State",
+                "Modified",
+                "Staged",
+                "Untracked",
+                "Path",
+            ]
+            .iter()
+            .map(|h| {
+                Cell::from(*h).style(
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )
+            }),
+        );
+        let rows: Vec<Row> = worktrees.iter().map(worktree_row).collect();
+        let widths = [
+            Constraint::Min(12),
+            Constraint::Min(15),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(8),
+            Constraint::Length(11),
+            Constraint::Min(20),
+        ];
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(block)
+            .style(Style::default().fg(Color::White))
+            .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        let mut state = TableState::default().with_selected(Some(self.selected));
+        f.render_stateful_widget(table, area, &mut state);
+    }
+}
+
+/// `locked`, `prunable` or nothing, for the State column.
+pub fn worktree_state(worktree: &WorktreeInfo) -> Cell<'static> {
+    if worktree.locked.is_some() {
+        Cell::from("locked").style(Style::default().fg(Color::Yellow))
+    } else if worktree.prunable {
+        Cell::from("prunable").style(Style::default().fg(Color::Red))
+    } else {
+        Cell::from("")
+    }
+}
+
+/// Checked-out branch, or `missing` when the worktree can't be opened.
+pub fn worktree_branch(worktree: &WorktreeInfo) -> Cell<'static> {
+    match &worktree.head {
+        Some(head) => Cell::from(head.label()).style(Style::default().fg(Color::Cyan)),
+        None => Cell::from("missing").style(Style::default().fg(Color::Red)),
+    }
+}
+
+fn worktree_row(worktree: &WorktreeInfo) -> Row<'static> {
+    Row::new(vec![
+        Cell::from(worktree.name.clone()),
+        worktree_branch(worktree),
+        worktree_state(worktree),
+        Cell::from(worktree.modified.to_string()),
+        Cell::from(worktree.staged.to_string()),
+        Cell::from(worktree.untracked.to_string()),
+        Cell::from(worktree.path.display().to_string()).style(Style::default().fg(Color::DarkGray)),
+    ])
+}
+
+/// `input` joined onto `base` unless absolute, with `.` and `..` folded
+/// away so the worktree is recorded under a clean path.
+fn resolve(base: &Path, input: &Path) -> PathBuf {
+    let mut path = PathBuf::new();
+    for component in base.join(input).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                path.pop();
+            }
+            other => path.push(other),
+        }
+    }
+    path
+}
@@ -1,11 +1,33 @@
+pub mod disk_usage_panel;
 pub mod git_panel;
+pub mod network_panel;
+pub mod process_panel;
 pub mod system_panel;
 
+use crossterm::event::KeyEvent;
 use ratatui::{layout::Rect, Frame};
 
+use crate::action::Action;
 use crate::source::DataSnapshot;
 
 pub trait Panel {
     fn on_data(&mut self, snapshot: &DataSnapshot);
     fn draw(&self, f: &mut Frame, area: Rect);
+
+    /// Keys not handled globally by `App` are forwarded to the active panel.
+    fn handle_key(&mut self, _key: KeyEvent) -> Option<Action> {
+        None
+    }
+
+    /// Called on the global refresh key; panels showing on-demand data
+    /// return the action that reloads it.
+    fn refresh(&mut self) -> Option<Action> {
+        None
+    }
+
+    /// While true (e.g. a text prompt is open) every key except Ctrl+C goes
+    /// to the panel, bypassing the global bindings.
+    fn captures_input(&self) -> bool {
+        false
+    }
 }
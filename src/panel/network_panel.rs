@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table},
+    Frame,
+};
+
+use crate::source::netstat::NetStatSnapshot;
+use crate::source::DataSnapshot;
+use crate::ui::format::format_bytes;
+
+use super::Panel;
+
+const RATE_HISTORY_LEN: usize = 60;
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+pub struct NetworkPanel {
+    snapshot: NetStatSnapshot,
+    rate_history: HashMap<&'static str, Vec<f64>>,
+}
+
+impl NetworkPanel {
+    pub fn new() -> Self {
+        Self {
+            snapshot: NetStatSnapshot::default(),
+            rate_history: HashMap::new(),
+        }
+    }
+
+    fn draw_sockets(&self, f: &mut Frame, area: Rect) {
+        let s = &self.snapshot.sockets;
+        let label = |text: &'static str| Span::styled(text, Style::default().fg(Color::Yellow));
+
+        let tcp_mem_style = if s.tcp_mem_pressure > 0 && s.tcp_mem_bytes >= s.tcp_mem_pressure {
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        let orphan_style = if s.tcp_orphan > 0 {
+            Style::default().fg(Color::Magenta)
+        } else {
+            Style::default()
+        };
+
+        let text = vec![
+            Line::from(vec![
+                label("Sockets "),
+                Span::raw(format!("{}  ", s.sockets_used)),
+                label("TCP in use "),
+                Span::raw(format!("{}  ", s.tcp_inuse)),
+                label("allocated "),
+                Span::raw(format!("{}  ", s.tcp_alloc)),
+                label("orphaned "),
+                Span::styled(format!("{}  ", s.tcp_orphan), orphan_style),
+                label("TIME_WAIT "),
+                Span::raw(format!("{}  ", s.tcp_time_wait)),
+                label("UDP in use "),
+                Span::raw(s.udp_inuse.to_string()),
+            ]),
+            Line::from(vec![
+                label("TCP memory "),
+                Span::styled(format_bytes(s.tcp_mem_bytes), tcp_mem_style),
+                Span::raw(format!(
+                    " (pressure at {}, max {})  ",
+                    format_bytes(s.tcp_mem_pressure),
+                    format_bytes(s.tcp_mem_max)
+                )),
+                label("UDP memory "),
+                Span::raw(format_bytes(s.udp_mem_bytes)),
+            ]),
+        ];
+
+        let paragraph =
+            Paragraph::new(text).block(Block::default().borders(Borders::ALL).title(" Sockets "));
+        f.render_widget(paragraph, area);
+    }
+
+    fn draw_counters(&self, f: &mut Frame, area: Rect) {
+        let header_cells = ["Counter", "Total", "Rate/s", "History"].iter().map(|h| {
+            Cell::from(*h).style(
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )
+        });
+        let header = Row::new(header_cells).height(1);
+
+        let rows: Vec<Row> = self
+            .snapshot
+            .counters
+            .iter()
+            .map(|c| {
+                let style = if c.alarming && c.rate > 0.0 {
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+                } else if c.alarming && c.total > 0 {
+                    Style::default().fg(Color::Yellow)
+                } else {
+                    Style::default()
+                };
+                let history = self
+                    .rate_history
+                    .get(c.label)
+                    .map(|h| sparkline(h))
+                    .unwrap_or_default();
+                Row::new(vec![
+                    Cell::from(c.label).style(style),
+                    Cell::from(c.total.to_string()),
+                    Cell::from(format!("{:.1}", c.rate)).style(style),
+                    Cell::from(history).style(Style::default().fg(Color::Green)),
+                ])
+            })
+            .collect();
+
+        let widths = [
+            Constraint::Length(28),
+            Constraint::Length(14),
+            Constraint::Length(10),
+            Constraint::Min(RATE_HISTORY_LEN as u16),
+        ];
+
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" TCP Stack Counters "),
+            )
+            .style(Style::default().fg(Color::White));
+
+        f.render_widget(table, area);
+    }
+}
+
+/// Render rates as a one-line bar chart scaled to the window's peak; idle
+/// samples are left blank so bursts stand out.
+fn sparkline(values: &[f64]) -> String {
+    let max = values.iter().copied().fold(0.0, f64::max);
+    values
+        .iter()
+        .map(|&v| {
+            if v <= 0.0 || max <= 0.0 {
+                ' '
+            } else {
+                let level = ((v / max) * (SPARK_LEVELS.len() - 1) as f64).round() as usize;
+                SPARK_LEVELS[level]
+            }
+        })
+        .collect()
+}
+
+impl Panel for NetworkPanel {
+    fn on_data(&mut self, data: &DataSnapshot) {
+        if let DataSnapshot::NetStat(snap) = data {
+            self.snapshot = snap.clone();
+
+            for counter in &snap.counters {
+                let history = self.rate_history.entry(counter.label).or_default();
+                history.push(counter.rate);
+                if history.len() > RATE_HISTORY_LEN {
+                    history.remove(0);
+                }
+            }
+        }
+    }
+
+    fn draw(&self, f: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(4), // socket gauges
+                Constraint::Min(0),    // counters
+            ])
+            .split(area);
+
+        self.draw_sockets(f, chunks[0]);
+        self.draw_counters(f, chunks[1]);
+    }
+}
@@ -0,0 +1,391 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+use crate::action::Action;
+use crate::source::holders::HoldersResult;
+use crate::source::process::ProcessSnapshot;
+use crate::source::smaps::{MemUsage, SmapsResult};
+use crate::source::DataSnapshot;
+use crate::ui::format::format_bytes;
+use crate::ui::input::TextInput;
+
+use super::Panel;
+
+/// What the right-hand pane shows: whichever lookup was requested last.
+enum Detail {
+    None,
+    Smaps(SmapsResult),
+    Holders(HoldersResult),
+}
+
+pub struct ProcessPanel {
+    snapshot: ProcessSnapshot,
+    selected: usize,
+    selected_pid: Option<u32>,
+    detail: Detail,
+    path_prompt: Option<TextInput>,
+}
+
+impl ProcessPanel {
+    pub fn new() -> Self {
+        Self {
+            snapshot: ProcessSnapshot::default(),
+            selected: 0,
+            selected_pid: None,
+            detail: Detail::None,
+            path_prompt: None,
+        }
+    }
+
+    fn select(&mut self, index: usize) {
+        if self.snapshot.processes.is_empty() {
+            self.selected = 0;
+            self.selected_pid = None;
+            return;
+        }
+        self.selected = index.min(self.snapshot.processes.len() - 1);
+        self.selected_pid = Some(self.snapshot.processes[self.selected].pid);
+    }
+
+    fn draw_process_table(&self, f: &mut Frame, area: Rect) {
+        let header_cells = ["PID", "Name", "CPU%", "Memory"].iter().map(|h| {
+            Cell::from(*h).style(
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )
+        });
+        let header = Row::new(header_cells).height(1);
+
+        let rows: Vec<Row> = self
+            .snapshot
+            .processes
+            .iter()
+            .map(|p| {
+                Row::new(vec![
+                    Cell::from(p.pid.to_string()),
+                    Cell::from(p.name.clone()).style(Style::default().fg(Color::Cyan)),
+                    Cell::from(format!("{:.1}", p.cpu_usage)),
+                    Cell::from(format_bytes(p.memory)),
+                ])
+            })
+            .collect();
+
+        let widths = [
+            Constraint::Length(8),
+            Constraint::Min(15),
+            Constraint::Length(7),
+            Constraint::Length(10),
+        ];
+
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Processes (Enter: memory map, f: find path users) "),
+            )
+            .style(Style::default().fg(Color::White))
+            .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        let mut state = TableState::default().with_selected(Some(self.selected));
+        f.render_stateful_widget(table, area, &mut state);
+    }
+
+    fn draw_hint(&self, f: &mut Frame, area: Rect) {
+        let hint = Paragraph::new(vec![
+            Line::from("Enter: read the selected process's memory map (smaps)"),
+            Line::from("f: find processes using a path (open files, cwd, mmaps, locks)"),
+        ])
+        .block(Block::default().borders(Borders::ALL).title(" Details "));
+        f.render_widget(hint, area);
+    }
+
+    fn draw_smaps(&self, f: &mut Frame, area: Rect, result: &SmapsResult) {
+        let title = format!(" Memory Map: PID {} ", result.pid);
+        let report = match &result.report {
+            Ok(report) => report,
+            Err(e) => {
+                let msg = Paragraph::new(Span::styled(e.clone(), Style::default().fg(Color::Red)))
+                    .block(Block::default().borders(Borders::ALL).title(title));
+                f.render_widget(msg, area);
+                return;
+            }
+        };
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),                               // totals
+                Constraint::Length(report.by_kind.len() as u16 + 3), // by kind
+                Constraint::Min(0),                                  // largest mappings
+            ])
+            .split(area);
+
+        let t = &report.total;
+        let totals = Paragraph::new(Line::from(vec![
+            Span::styled("RSS ", Style::default().fg(Color::Yellow)),
+            Span::raw(format!("{}  ", format_bytes(t.rss))),
+            Span::styled("PSS ", Style::default().fg(Color::Yellow)),
+            Span::raw(format!("{}  ", format_bytes(t.pss))),
+            Span::styled("USS ", Style::default().fg(Color::Yellow)),
+            Span::raw(format!("{}  ", format_bytes(t.uss))),
+            Span::styled("Swap ", Style::default().fg(Color::Yellow)),
+            Span::raw(format_bytes(t.swap)),
+        ]))
+        .block(Block::default().borders(Borders::ALL).title(title));
+        f.render_widget(totals, chunks[0]);
+
+        let kind_rows: Vec<Row> = report
+            .by_kind
+            .iter()
+            .map(|(kind, usage)| usage_row(Cell::from(kind.label()), usage))
+            .collect();
+        let kind_table = Table::new(
+            kind_rows,
+            [
+                Constraint::Min(12),
+                Constraint::Length(10),
+                Constraint::Length(10),
+                Constraint::Length(10),
+            ],
+        )
+        .header(usage_header("Kind"))
+        .block(Block::default().borders(Borders::ALL).title(" By Kind "));
+        f.render_widget(kind_table, chunks[1]);
+
+        let mapping_rows: Vec<Row> = report
+            .largest
+            .iter()
+            .map(|m| {
+                let label = if m.path.is_empty() {
+                    format!("{} {}", m.range, m.perms)
+                } else {
+                    format!("{} {}", m.path, m.perms)
+                };
+                usage_row(Cell::from(label), &m.usage)
+            })
+            .collect();
+        let mapping_table = Table::new(
+            mapping_rows,
+            [
+                Constraint::Min(20),
+                Constraint::Length(10),
+                Constraint::Length(10),
+                Constraint::Length(10),
+            ],
+        )
+        .header(usage_header("Mapping"))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Largest Mappings (by PSS) "),
+        );
+        f.render_widget(mapping_table, chunks[2]);
+    }
+
+    fn draw_holders(&self, f: &mut Frame, area: Rect, result: &HoldersResult) {
+        let title = format!(" Users of {} ", result.query.display());
+        let report = match &result.report {
+            Ok(report) => report,
+            Err(e) => {
+                let msg = Paragraph::new(Span::styled(e.clone(), Style::default().fg(Color::Red)))
+                    .block(Block::default().borders(Borders::ALL).title(title));
+                f.render_widget(msg, area);
+                return;
+            }
+        };
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(5),                                             // holders
+                Constraint::Length(report.locks.len().clamp(1, 10) as u16 + 3), // locks
+            ])
+            .split(area);
+
+        let holder_rows: Vec<Row> = report
+            .holders
+            .iter()
+            .map(|h| {
+                Row::new(vec![
+                    Cell::from(h.pid.to_string()),
+                    Cell::from(h.name.clone()).style(Style::default().fg(Color::Cyan)),
+                    Cell::from(h.kind.label()),
+                    Cell::from(h.target.clone()),
+                ])
+            })
+            .collect();
+        let holders_title = if report.skipped > 0 {
+            format!("{title}({} processes unreadable) ", report.skipped)
+        } else {
+            title
+        };
+        let holder_table = Table::new(
+            holder_rows,
+            [
+                Constraint::Length(8),
+                Constraint::Length(16),
+                Constraint::Length(7),
+                Constraint::Min(20),
+            ],
+        )
+        .header(header_row(&["PID", "Name", "Use", "Path"]))
+        .block(Block::default().borders(Borders::ALL).title(holders_title));
+        f.render_widget(holder_table, chunks[0]);
+
+        let lock_rows: Vec<Row> = report
+            .locks
+            .iter()
+            .map(|l| {
+                let state = if l.waiting { "waiting" } else { "held" };
+                Row::new(vec![
+                    Cell::from(l.pid.to_string()),
+                    Cell::from(l.name.clone()).style(Style::default().fg(Color::Cyan)),
+                    Cell::from(format!("{} {}", l.class, l.mode)),
+                    Cell::from(state).style(if l.waiting {
+                        Style::default().fg(Color::Red)
+                    } else {
+                        Style::default().fg(Color::Yellow)
+                    }),
+                    Cell::from(l.target.clone()),
+                ])
+            })
+            .collect();
+        let lock_table = Table::new(
+            lock_rows,
+            [
+                Constraint::Length(8),
+                Constraint::Length(16),
+                Constraint::Length(12),
+                Constraint::Length(8),
+                Constraint::Min(20),
+            ],
+        )
+        .header(header_row(&["PID", "Name", "Lock", "State", "File"]))
+        .block(Block::default().borders(Borders::ALL).title(" File Locks "));
+        f.render_widget(lock_table, chunks[1]);
+    }
+}
+
+fn header_row(titles: &[&'static str]) -> Row<'static> {
+    Row::new(titles.iter().map(|h| {
+        Cell::from(*h).style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+    }))
+}
+
+fn usage_header(first: &'static str) -> Row<'static> {
+    header_row(&[first, "RSS", "PSS", "USS"])
+}
+
+fn usage_row<'a>(label: Cell<'a>, usage: &MemUsage) -> Row<'a> {
+    Row::new(vec![
+        label,
+        Cell::from(format_bytes(usage.rss)),
+        Cell::from(format_bytes(usage.pss)).style(Style::default().fg(Color::Cyan)),
+        Cell::from(format_bytes(usage.uss)),
+    ])
+}
+
+impl Panel for ProcessPanel {
+    fn on_data(&mut self, data: &DataSnapshot) {
+        match data {
+            DataSnapshot::Process(snap) => {
+                self.snapshot = snap.clone();
+                // Keep the cursor on the same process across re-sorts
+                let index = self
+                    .selected_pid
+                    .and_then(|pid| self.snapshot.processes.iter().position(|p| p.pid == pid))
+                    .unwrap_or(self.selected);
+                self.select(index);
+            }
+            DataSnapshot::Smaps(result) => {
+                self.detail = Detail::Smaps(result.clone());
+            }
+            DataSnapshot::Holders(result) => {
+                self.detail = Detail::Holders(result.clone());
+            }
+            _ => {}
+        }
+    }
+
+    fn draw(&self, f: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+            .split(area);
+
+        let left = match &self.path_prompt {
+            Some(input) => {
+                let split = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(3), Constraint::Length(3)])
+                    .split(chunks[0]);
+                input.draw(
+                    f,
+                    split[1],
+                    "Find users of path (Enter: search, Esc: cancel)",
+                );
+                split[0]
+            }
+            None => chunks[0],
+        };
+
+        self.draw_process_table(f, left);
+        match &self.detail {
+            Detail::None => self.draw_hint(f, chunks[1]),
+            Detail::Smaps(result) => self.draw_smaps(f, chunks[1], result),
+            Detail::Holders(result) => self.draw_holders(f, chunks[1], result),
+        }
+    }
+
+    fn captures_input(&self) -> bool {
+        self.path_prompt.is_some()
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Option<Action> {
+        if let Some(input) = &mut self.path_prompt {
+            match key.code {
+                KeyCode::Esc => self.path_prompt = None,
+                KeyCode::Enter => {
+                    let path = input.value().trim().to_string();
+                    self.path_prompt = None;
+                    if !path.is_empty() {
+                        return Some(Action::FindPathHolders(path.into()));
+                    }
+                }
+                _ => {
+                    input.handle_key(key);
+                }
+            }
+            return None;
+        }
+
+        match key.code {
+            KeyCode::Char('f') => {
+                self.path_prompt = Some(TextInput::default());
+                None
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.select(self.selected.saturating_sub(1));
+                None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.select(self.selected + 1);
+                None
+            }
+            KeyCode::Enter => self.selected_pid.map(Action::InspectMemory),
+            _ => None,
+        }
+    }
+}
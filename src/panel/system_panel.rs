@@ -1,14 +1,19 @@
+use std::time::Duration;
+
+use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     symbols,
     text::Span,
-    widgets::{Block, Borders, Gauge, Sparkline},
+    widgets::{Block, Borders, Cell, Gauge, Row, Sparkline, Table, TableState},
     Frame,
 };
 
+use crate::action::Action;
 use crate::source::system::SystemSnapshot;
 use crate::source::DataSnapshot;
+use crate::ui::format::{format_bytes, format_duration};
 
 use super::Panel;
 
@@ -17,15 +22,96 @@ const CPU_HISTORY_LEN: usize = 60;
 pub struct SystemPanel {
     snapshot: SystemSnapshot,
     cpu_history: Vec<u64>,
+    forecast_horizon: Duration,
+    /// Position in the filesystems table
+    selected: usize,
 }
 
 impl SystemPanel {
-    pub fn new() -> Self {
+    pub fn new(forecast_horizon: Duration) -> Self {
         Self {
             snapshot: SystemSnapshot::default(),
             cpu_history: Vec::with_capacity(CPU_HISTORY_LEN),
+            forecast_horizon,
+            selected: 0,
         }
     }
+
+    /// Status bar warning for the filesystem forecast to fill soonest, if it
+    /// is within the configured horizon.
+    pub fn disk_warning(&self) -> Option<String> {
+        self.snapshot
+            .filesystems
+            .iter()
+            .filter_map(|fs| fs.time_to_full.map(|t| (fs, t)))
+            .filter(|(_, t)| *t <= self.forecast_horizon)
+            .min_by_key(|(_, t)| *t)
+            .map(|(fs, t)| format!("{} full in ~{}", fs.mount_point, format_duration(t)))
+    }
+
+    fn draw_filesystems(&self, f: &mut Frame, area: Rect) {
+        let header_cells = ["Mount", "Used", "Total", "Use%", "Forecast"]
+            .iter()
+            .map(|h| {
+                Cell::from(*h).style(
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )
+            });
+        let header = Row::new(header_cells).height(1);
+
+        let rows: Vec<Row> = self
+            .snapshot
+            .filesystems
+            .iter()
+            .map(|fs| {
+                let pct = if fs.total == 0 {
+                    0.0
+                } else {
+                    fs.used as f64 / fs.total as f64 * 100.0
+                };
+                let forecast = match fs.time_to_full {
+                    Some(t) => Cell::from(format!("full in ~{}", format_duration(t))).style(
+                        if t <= self.forecast_horizon {
+                            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+                        } else {
+                            Style::default().fg(Color::Yellow)
+                        },
+                    ),
+                    None => Cell::from("stable").style(Style::default().fg(Color::Green)),
+                };
+                Row::new(vec![
+                    Cell::from(fs.mount_point.clone()).style(Style::default().fg(Color::Cyan)),
+                    Cell::from(format_bytes(fs.used)),
+                    Cell::from(format_bytes(fs.total)),
+                    Cell::from(format!("{pct:.1}%")),
+                    forecast,
+                ])
+            })
+            .collect();
+
+        let widths = [
+            Constraint::Min(20),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(7),
+            Constraint::Length(18),
+        ];
+
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Filesystems (u: analyze disk usage) "),
+            )
+            .style(Style::default().fg(Color::White))
+            .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        let mut state = TableState::default().with_selected(Some(self.selected));
+        f.render_stateful_widget(table, area, &mut state);
+    }
 }
 
 impl Panel for SystemPanel {
@@ -43,7 +129,26 @@ impl Panel for SystemPanel {
             if self.cpu_history.len() > CPU_HISTORY_LEN {
                 self.cpu_history.remove(0);
             }
+            self.selected = self
+                .selected
+                .min(self.snapshot.filesystems.len().saturating_sub(1));
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Option<Action> {
+        let len = self.snapshot.filesystems.len();
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(len.saturating_sub(1));
+            }
+            KeyCode::Enter | KeyCode::Char('u') => {
+                let fs = self.snapshot.filesystems.get(self.selected)?;
+                return Some(Action::AnalyzeMount(fs.mount_point.clone().into()));
+            }
+            _ => {}
         }
+        None
     }
 
     fn draw(&self, f: &mut Frame, area: Rect) {
@@ -53,7 +158,7 @@ impl Panel for SystemPanel {
                 Constraint::Length(4), // CPU sparkline
                 Constraint::Length(3), // Memory gauge
                 Constraint::Length(3), // Disk gauge
-                Constraint::Min(0),   // spacer
+                Constraint::Min(0),    // filesystems
             ])
             .split(area);
 
@@ -88,11 +193,7 @@ impl Panel for SystemPanel {
         let mem_total_mb = self.snapshot.total_memory / (1024 * 1024);
 
         let mem_gauge = Gauge::default()
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .title(" Memory "),
-            )
+            .block(Block::default().borders(Borders::ALL).title(" Memory "))
             .gauge_style(
                 Style::default()
                     .fg(Color::Cyan)
@@ -117,11 +218,7 @@ impl Panel for SystemPanel {
         let disk_total_gb = self.snapshot.total_disk / (1024 * 1024 * 1024);
 
         let disk_gauge = Gauge::default()
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .title(" Disk "),
-            )
+            .block(Block::default().borders(Borders::ALL).title(" Disk "))
             .gauge_style(
                 Style::default()
                     .fg(Color::Yellow)
@@ -134,5 +231,7 @@ impl Panel for SystemPanel {
             )));
 
         f.render_widget(disk_gauge, chunks[2]);
+
+        self.draw_filesystems(f, chunks[3]);
     }
 }
@@ -0,0 +1,270 @@
+use std::collections::HashSet;
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use globset::GlobSet;
+
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone)]
+pub enum DuEvent {
+    Progress(DuProgress),
+    Done(std::result::Result<Arc<DuTree>, String>),
+    Removed {
+        path: PathBuf,
+        result: std::result::Result<(), String>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct DuProgress {
+    pub root: PathBuf,
+    pub entries: u64,
+    pub bytes: u64,
+}
+
+/// Directory tree stored as an arena; node 0 is the scan root.
+#[derive(Debug, Clone, Default)]
+pub struct DuTree {
+    pub nodes: Vec<DuNode>,
+    /// Entries that could not be read (usually EACCES).
+    pub errors: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct DuNode {
+    pub name: String,
+    /// Disk usage (allocated blocks), including all descendants.
+    pub size: u64,
+    pub is_dir: bool,
+    pub parent: Option<usize>,
+    /// Sorted by size, largest first.
+    pub children: Vec<usize>,
+}
+
+impl DuTree {
+    pub fn path(&self, mut index: usize) -> PathBuf {
+        let mut parts = vec![self.nodes[index].name.as_str()];
+        while let Some(parent) = self.nodes[index].parent {
+            parts.push(&self.nodes[parent].name);
+            index = parent;
+        }
+        parts.iter().rev().collect()
+    }
+
+    /// Detach a node after it was deleted on disk, subtracting its size from
+    /// every ancestor.
+    pub fn remove(&mut self, index: usize) {
+        let size = self.nodes[index].size;
+        let Some(parent) = self.nodes[index].parent else {
+            return;
+        };
+        self.nodes[parent].children.retain(|&c| c != index);
+
+        let mut ancestor = Some(parent);
+        while let Some(a) = ancestor {
+            self.nodes[a].size = self.nodes[a].size.saturating_sub(size);
+            ancestor = self.nodes[a].parent;
+        }
+    }
+
+    pub fn find(&self, path: &Path) -> Option<usize> {
+        let root = Path::new(&self.nodes.first()?.name);
+        let rel = path.strip_prefix(root).ok()?;
+        rel.components().try_fold(0, |index, component| {
+            let name = component.as_os_str().to_string_lossy();
+            self.nodes[index]
+                .children
+                .iter()
+                .copied()
+                .find(|&c| self.nodes[c].name == name)
+        })
+    }
+}
+
+struct Scanner<'a, F> {
+    tree: DuTree,
+    excludes: &'a GlobSet,
+    device: u64,
+    seen_inodes: HashSet<(u64, u64)>,
+    cancel: &'a AtomicBool,
+    on_progress: F,
+    root: PathBuf,
+    last_progress: Instant,
+}
+
+impl<F: FnMut(DuProgress)> Scanner<'_, F> {
+    fn scan_dir(&mut self, index: usize, path: &Path) {
+        let Ok(entries) = fs::read_dir(path) else {
+            self.tree.errors += 1;
+            return;
+        };
+
+        for entry in entries {
+            if self.cancel.load(Ordering::Relaxed) {
+                return;
+            }
+            let Ok(entry) = entry else {
+                self.tree.errors += 1;
+                continue;
+            };
+            let child_path = entry.path();
+            if self.excludes.is_match(&child_path) {
+                continue;
+            }
+            // symlink_metadata: never follow links out of the tree
+            let Ok(meta) = fs::symlink_metadata(&child_path) else {
+                self.tree.errors += 1;
+                continue;
+            };
+            // Stay on one filesystem, like `du -x`
+            if meta.dev() != self.device {
+                continue;
+            }
+            // Count hard-linked files once
+            if meta.nlink() > 1
+                && !meta.is_dir()
+                && !self.seen_inodes.insert((meta.dev(), meta.ino()))
+            {
+                continue;
+            }
+
+            let child = self.tree.nodes.len();
+            self.tree.nodes.push(DuNode {
+                name: entry.file_name().to_string_lossy().to_string(),
+                size: meta.blocks() * 512,
+                is_dir: meta.is_dir(),
+                parent: Some(index),
+                children: Vec::new(),
+            });
+            self.tree.nodes[index].children.push(child);
+
+            if meta.is_dir() {
+                self.scan_dir(child, &child_path);
+            }
+            let size = self.tree.nodes[child].size;
+            self.tree.nodes[index].size += size;
+
+            if self.last_progress.elapsed() >= PROGRESS_INTERVAL {
+                self.last_progress = Instant::now();
+                (self.on_progress)(DuProgress {
+                    root: self.root.clone(),
+                    entries: self.tree.nodes.len() as u64,
+                    bytes: self.tree.nodes[0].size,
+                });
+            }
+        }
+
+        let mut children = std::mem::take(&mut self.tree.nodes[index].children);
+        children.sort_by_key(|&c| std::cmp::Reverse(self.tree.nodes[c].size));
+        self.tree.nodes[index].children = children;
+    }
+}
+
+/// Walk `root` without crossing filesystem boundaries, skipping paths that
+/// match `excludes`. Returns `None` if cancelled.
+pub fn scan(
+    root: &Path,
+    excludes: &GlobSet,
+    cancel: &AtomicBool,
+    on_progress: impl FnMut(DuProgress),
+) -> std::io::Result<Option<DuTree>> {
+    let root = fs::canonicalize(root)?;
+    let meta = fs::metadata(&root)?;
+
+    let mut scanner = Scanner {
+        tree: DuTree::default(),
+        excludes,
+        device: meta.dev(),
+        seen_inodes: HashSet::new(),
+        cancel,
+        on_progress,
+        root: root.clone(),
+        last_progress: Instant::now(),
+    };
+    scanner.tree.nodes.push(DuNode {
+        name: root.to_string_lossy().to_string(),
+        size: meta.blocks() * 512,
+        is_dir: meta.is_dir(),
+        parent: None,
+        children: Vec::new(),
+    });
+    if meta.is_dir() {
+        scanner.scan_dir(0, &root);
+    }
+
+    if cancel.load(Ordering::Relaxed) {
+        return Ok(None);
+    }
+    Ok(Some(scanner.tree))
+}
+
+/// Permanently delete a file or directory tree.
+pub fn delete(path: &Path) -> std::io::Result<()> {
+    let meta = fs::symlink_metadata(path)?;
+    if meta.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Move a path into the user's trash following the freedesktop.org Trash
+/// spec (`$XDG_DATA_HOME/Trash/{files,info}`). Only the home trash is
+/// supported, so paths on other filesystems are refused rather than copied.
+pub fn trash(path: &Path) -> std::io::Result<()> {
+    let trash_dir = super::data_home()?.join("Trash");
+    let files_dir = trash_dir.join("files");
+    let info_dir = trash_dir.join("info");
+    fs::create_dir_all(&files_dir)?;
+    fs::create_dir_all(&info_dir)?;
+
+    let source_dev = fs::symlink_metadata(path)?.dev();
+    if fs::metadata(&files_dir)?.dev() != source_dev {
+        return Err(std::io::Error::other(
+            "path is on a different filesystem than the trash; delete it instead",
+        ));
+    }
+
+    let base = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "trashed".to_string());
+    let mut name = base.clone();
+    let mut n = 1;
+    while files_dir.join(&name).exists() || info_dir.join(format!("{name}.trashinfo")).exists() {
+        n += 1;
+        name = format!("{base}.{n}");
+    }
+
+    let info = format!(
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        percent_encode(path),
+        chrono::Local::now().format("%Y-%m-%dT%H:%M:%S")
+    );
+    fs::write(info_dir.join(format!("{name}.trashinfo")), info)?;
+    if let Err(e) = fs::rename(path, files_dir.join(&name)) {
+        let _ = fs::remove_file(info_dir.join(format!("{name}.trashinfo")));
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Escape a path the way the Trash spec wants `Path=` written: RFC 2396
+/// percent-encoding of every byte that isn't unreserved or a separator.
+fn percent_encode(path: &Path) -> String {
+    let mut out = String::new();
+    for &byte in path.as_os_str().as_bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-_.!~*'()".contains(&byte) {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    out
+}
@@ -0,0 +1,108 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasher, RandomState};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, Instant};
+
+use tokio::sync::mpsc;
+
+use super::remote::{self, RemoteOp};
+use super::{RepoEntry, Upstream};
+use crate::config::GitConfig;
+
+/// Fetches repos in the background so ahead/behind counts don't go stale
+/// between manual fetches. Driven from `GitSource::collect`.
+pub struct AutoFetch {
+    config: GitConfig,
+    /// Seeded per run, so each repo gets a stable but unpredictable offset
+    jitter: RandomState,
+    running: HashSet<PathBuf>,
+    /// Earliest next attempt, so a failing fetch isn't retried every poll
+    retry_at: HashMap<PathBuf, Instant>,
+    /// Why the last fetch of a repo failed, until one succeeds
+    failed: HashMap<PathBuf, String>,
+    done_tx: mpsc::UnboundedSender<(PathBuf, Result<(), String>)>,
+    done_rx: mpsc::UnboundedReceiver<(PathBuf, Result<(), String>)>,
+}
+
+impl AutoFetch {
+    pub fn new(config: &GitConfig) -> Self {
+        let (done_tx, done_rx) = mpsc::unbounded_channel();
+        Self {
+            config: config.clone(),
+            jitter: RandomState::new(),
+            running: HashSet::new(),
+            retry_at: HashMap::new(),
+            failed: HashMap::new(),
+            done_tx,
+            done_rx,
+        }
+    }
+
+    fn jitter(&self, path: &Path) -> u64 {
+        self.jitter.hash_one(path) % (self.config.auto_fetch_jitter_secs + 1)
+    }
+
+    /// Collect fetches that finished since the last call.
+    pub fn reap(&mut self) {
+        while let Ok((path, result)) = self.done_rx.try_recv() {
+            self.running.remove(&path);
+            match result {
+                Ok(()) => self.failed.remove(&path),
+                Err(message) => self.failed.insert(path, message),
+            };
+        }
+    }
+
+    /// Why the last background fetch of `path` failed, if it did.
+    pub fn error(&self, path: &Path) -> Option<&str> {
+        self.failed.get(path).map(String::as_str)
+    }
+
+    /// Start fetches for repos whose last fetch is older than their interval
+    /// plus jitter, keeping at most `auto_fetch_concurrency` running. Only
+    /// repos with an upstream are fetched; nothing else would show it.
+    pub fn schedule(&mut self, repos: &[RepoEntry]) {
+        let now = Instant::now();
+        let now_secs = chrono::Utc::now().timestamp();
+        for entry in repos {
+            if self.running.len() >= self.config.auto_fetch_concurrency {
+                break;
+            }
+            let Some(interval) = self.config.auto_fetch_secs(&entry.path) else {
+                continue;
+            };
+            let Some(status) = entry.ok_status() else {
+                continue;
+            };
+            if !matches!(status.upstream, Upstream::Tracking { .. })
+                || self.running.contains(&entry.path)
+                || self.retry_at.get(&entry.path).is_some_and(|at| *at > now)
+            {
+                continue;
+            }
+            let due = interval + self.jitter(&entry.path);
+            let age = status
+                .last_fetched
+                .map_or(u64::MAX, |at| (now_secs - at).max(0) as u64);
+            if age < due {
+                continue;
+            }
+
+            self.running.insert(entry.path.clone());
+            self.retry_at
+                .insert(entry.path.clone(), now + Duration::from_secs(due));
+            spawn_fetch(entry.path.clone(), self.done_tx.clone());
+        }
+    }
+}
+
+fn spawn_fetch(path: PathBuf, done: mpsc::UnboundedSender<(PathBuf, Result<(), String>)>) {
+    tokio::task::spawn_blocking(move || {
+        let cancel = AtomicBool::new(false);
+        let result = remote::run(&path, RemoteOp::Fetch, &cancel, |_| {})
+            .map(|_| ())
+            .map_err(|e| e.message().to_string());
+        let _ = done.send((path, result));
+    });
+}
@@ -0,0 +1,283 @@
+use std::path::Path;
+
+use git2::build::CheckoutBuilder;
+use git2::{BranchType, Oid, Repository, StatusOptions};
+
+use super::{resolve_upstream, Upstream};
+
+#[derive(Debug, Clone)]
+pub struct BranchList {
+    /// Local branches first, then remote ones, each sorted by name
+    pub branches: Vec<BranchInfo>,
+    /// Branch that `merged` is measured against
+    pub default: Option<String>,
+    /// Tracked files have uncommitted changes
+    pub dirty: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct BranchInfo {
+    pub name: String,
+    pub remote: bool,
+    /// Currently checked out
+    pub head: bool,
+    pub upstream: Upstream,
+    pub target: Oid,
+    /// Seconds since the epoch, of the tip commit
+    pub time: i64,
+    pub summary: String,
+    /// Tip is reachable from the default branch
+    pub merged: bool,
+}
+
+/// A branch change requested from the branches or log view.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BranchOp {
+    /// Check out a local branch, or a local branch tracking a remote one.
+    /// A dirty tree is refused unless `stash` is set.
+    Checkout {
+        name: String,
+        remote: bool,
+        stash: bool,
+    },
+    Create {
+        name: String,
+        from: Oid,
+    },
+    Rename {
+        from: String,
+        to: String,
+    },
+    /// Refuses branches not merged into the default branch unless `force`
+    Delete {
+        name: String,
+        force: bool,
+    },
+}
+
+impl BranchOp {
+    pub fn describe(&self) -> String {
+        match self {
+            BranchOp::Checkout { name, .. } => format!("check out {name}"),
+            BranchOp::Create { name, .. } => format!("create branch {name}"),
+            BranchOp::Rename { from, to } => format!("rename {from} to {to}"),
+            BranchOp::Delete { name, .. } => format!("delete branch {name}"),
+        }
+    }
+}
+
+/// The branch others merge into: whatever `origin/HEAD` points at (its local
+/// counterpart if there is one), else `main` or `master`, else HEAD.
+pub(super) fn default_branch(repo: &Repository) -> Option<(String, Oid)> {
+    let mut candidates = Vec::new();
+    if let Some(target) = repo
+        .find_reference("refs/remotes/origin/HEAD")
+        .ok()
+        .and_then(|r| r.symbolic_target().map(str::to_string))
+    {
+        if let Some((_, branch)) = target
+            .strip_prefix("refs/remotes/")
+            .and_then(|name| name.split_once('/'))
+        {
+            candidates.push(format!("refs/heads/{branch}"));
+        }
+        candidates.push(target);
+    }
+    candidates.push("refs/heads/main".to_string());
+    candidates.push("refs/heads/master".to_string());
+    if let Some(head) = repo.head().ok().filter(|h| h.is_branch()) {
+        candidates.extend(head.name().map(str::to_string));
+    }
+
+    candidates.into_iter().find_map(|refname| {
+        let oid = repo.refname_to_id(&refname).ok()?;
+        let name = refname
+            .strip_prefix("refs/heads/")
+            .or_else(|| refname.strip_prefix("refs/remotes/"))
+            .unwrap_or(&refname)
+            .to_string();
+        Some((name, oid))
+    })
+}
+
+pub(super) fn is_merged(repo: &Repository, base: Oid, tip: Oid) -> bool {
+    base == tip || repo.graph_descendant_of(base, tip).unwrap_or(false)
+}
+
+/// Tracked files differ from HEAD, in the index or the worktree.
+fn is_dirty(repo: &Repository) -> Result<bool, git2::Error> {
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(false).include_ignored(false);
+    Ok(!repo.statuses(Some(&mut opts))?.is_empty())
+}
+
+pub fn list_branches(path: &Path) -> Result<BranchList, git2::Error> {
+    let repo = Repository::open(path)?;
+    let default = default_branch(&repo);
+
+    let mut branches = Vec::new();
+    for branch in repo.branches(None)? {
+        let (branch, kind) = branch?;
+        let (Some(name), Some(target)) = (branch.name()?, branch.get().target()) else {
+            continue;
+        };
+        let remote = kind == BranchType::Remote;
+        // origin/HEAD just repeats the default branch
+        if remote && name.ends_with("/HEAD") {
+            continue;
+        }
+        let commit = repo.find_commit(target)?;
+        // The default branch is merged into itself; don't offer to delete it
+        let is_default = default.as_ref().is_some_and(|(d, _)| d == name);
+        branches.push(BranchInfo {
+            name: name.to_string(),
+            remote,
+            head: branch.is_head(),
+            upstream: if remote {
+                Upstream::None
+            } else {
+                resolve_upstream(&repo, name)?
+            },
+            target,
+            time: commit.time().seconds(),
+            summary: commit.summary().unwrap_or_default().to_string(),
+            merged: !is_default
+                && default
+                    .as_ref()
+                    .is_some_and(|(_, base)| is_merged(&repo, *base, target)),
+        });
+    }
+    branches.sort_by(|a, b| (a.remote, &a.name).cmp(&(b.remote, &b.name)));
+
+    Ok(BranchList {
+        branches,
+        default: default.map(|(name, _)| name),
+        dirty: is_dirty(&repo)?,
+    })
+}
+
+/// Local branch to check out for remote branch `name` (`origin/foo` gives
+/// `foo`), created to track it if it doesn't exist yet.
+fn local_for_remote(repo: &Repository, name: &str) -> Result<String, git2::Error> {
+    let (_, short) = name
+        .split_once('/')
+        .ok_or_else(|| git2::Error::from_str(&format!("{name} is not a remote branch")))?;
+    if repo.find_branch(short, BranchType::Local).is_err() {
+        let tip = repo
+            .find_branch(name, BranchType::Remote)?
+            .get()
+            .peel_to_commit()?;
+        repo.branch(short, &tip, false)?.set_upstream(Some(name))?;
+    }
+    Ok(short.to_string())
+}
+
+/// Returns whether uncommitted changes were stashed first.
+fn checkout(
+    repo: &mut Repository,
+    name: &str,
+    remote: bool,
+    stash: bool,
+) -> Result<bool, git2::Error> {
+    // Resolve the target first, so a bad name fails before anything is stashed
+    let local = if remote {
+        local_for_remote(repo, name)?
+    } else {
+        name.to_string()
+    };
+    let refname = format!("refs/heads/{local}");
+    let target = repo
+        .find_reference(&refname)?
+        .peel(git2::ObjectType::Commit)?
+        .id();
+
+    let mut stashed = false;
+    if is_dirty(repo)? {
+        if !stash {
+            return Err(git2::Error::from_str(
+                "uncommitted changes would be overwritten; commit or stash them first",
+            ));
+        }
+        let signature = repo.signature()?;
+        repo.stash_save(
+            &signature,
+            &format!("ktop: before checking out {name}"),
+            None,
+        )?;
+        stashed = true;
+    }
+
+    let result = repo.find_object(target, None).and_then(|target| {
+        repo.checkout_tree(&target, Some(CheckoutBuilder::new().safe()))?;
+        repo.set_head(&refname)
+    });
+    match result {
+        Ok(()) => Ok(stashed),
+        Err(e) if stashed => Err(match repo.stash_pop(0, None) {
+            Ok(()) => e,
+            Err(_) => git2::Error::from_str(&format!(
+                "{}; your changes are still in stash@{{0}}",
+                e.message()
+            )),
+        }),
+        Err(e) => Err(e),
+    }
+}
+
+fn delete(repo: &Repository, name: &str, force: bool) -> Result<(), git2::Error> {
+    let mut branch = repo.find_branch(name, BranchType::Local)?;
+    if branch.is_head() {
+        return Err(git2::Error::from_str(&format!(
+            "cannot delete {name} while it is checked out"
+        )));
+    }
+    if !force {
+        let tip = branch
+            .get()
+            .target()
+            .ok_or_else(|| git2::Error::from_str(&format!("{name} has no target")))?;
+        let Some((base_name, base)) = default_branch(repo) else {
+            return Err(git2::Error::from_str(&format!(
+                "can't tell whether {name} is merged without a default branch; force-delete it instead"
+            )));
+        };
+        if base_name == name {
+            return Err(git2::Error::from_str(&format!(
+                "{name} is the default branch; force-delete it instead"
+            )));
+        }
+        if !is_merged(repo, base, tip) {
+            return Err(git2::Error::from_str(&format!(
+                "{name} is not merged into {base_name}"
+            )));
+        }
+    }
+    branch.delete()
+}
+
+/// Blocking; run through `GitRequest::Branch`. Returns a note worth showing
+/// on success, like where uncommitted changes were stashed.
+pub fn apply(path: &Path, op: &BranchOp) -> Result<Option<String>, git2::Error> {
+    let mut repo = Repository::open(path)?;
+    match op {
+        BranchOp::Checkout {
+            name,
+            remote,
+            stash,
+        } => {
+            let stashed = checkout(&mut repo, name, *remote, *stash)?;
+            Ok(stashed.then(|| "changes saved to stash@{0}".to_string()))
+        }
+        BranchOp::Create { name, from } => {
+            let commit = repo.find_commit(*from)?;
+            repo.branch(name, &commit, false)?;
+            Ok(None)
+        }
+        BranchOp::Rename { from, to } => {
+            repo.find_branch(from, BranchType::Local)?
+                .rename(to, false)?;
+            Ok(None)
+        }
+        BranchOp::Delete { name, force } => delete(&repo, name, *force).map(|()| None),
+    }
+}
@@ -0,0 +1,160 @@
+use std::path::Path;
+
+use git2::{Oid, Repository, RepositoryState};
+
+use super::files::{collect_files, FileChange, FileSection};
+use crate::config::StatusConfig;
+
+/// What the commit composer shows alongside the editor.
+#[derive(Debug, Clone)]
+pub struct CommitDraft {
+    pub staged: Vec<FileChange>,
+    /// Message of HEAD, offered when amending
+    pub head_message: Option<String>,
+    /// `Name <email>` from git config, or why it can't be determined
+    pub signature: Result<String, String>,
+}
+
+pub fn prepare(path: &Path) -> Result<CommitDraft, git2::Error> {
+    let repo = Repository::open(path)?;
+    // Everything staged gets committed, whatever the repo's pathspecs say
+    let only_staged = StatusConfig {
+        include_untracked: false,
+        ..StatusConfig::default()
+    };
+    let staged = collect_files(path, &only_staged)?
+        .into_iter()
+        .filter(|f| f.section == FileSection::Staged)
+        .collect();
+    let head_message = repo
+        .head()
+        .ok()
+        .and_then(|h| h.peel_to_commit().ok())
+        .and_then(|c| c.message().map(str::to_string));
+    let signature = repo
+        .signature()
+        .map(|s| {
+            format!(
+                "{} <{}>",
+                s.name().unwrap_or_default(),
+                s.email().unwrap_or_default()
+            )
+        })
+        .map_err(|e| e.message().to_string());
+
+    Ok(CommitDraft {
+        staged,
+        head_message,
+        signature,
+    })
+}
+
+/// Commit the index with the configured signature, or amend HEAD with it.
+/// The message is cleaned up like `git commit` does: `#` lines and trailing
+/// whitespace are dropped. Returns the new commit id.
+pub fn commit(path: &Path, message: &str, amend: bool) -> Result<Oid, git2::Error> {
+    let repo = Repository::open(path)?;
+    let message = git2::message_prettify(message, Some(b'#'))?;
+    if message.trim().is_empty() {
+        return Err(git2::Error::from_str(
+            "aborting commit due to empty message",
+        ));
+    }
+
+    // Amending leaves any operation in progress alone; a new commit only
+    // finishes a merge or a single cherry-pick or revert
+    let state = repo.state();
+    if !amend
+        && !matches!(
+            state,
+            RepositoryState::Clean
+                | RepositoryState::Merge
+                | RepositoryState::CherryPick
+                | RepositoryState::Revert
+        )
+    {
+        return Err(git2::Error::from_str(&format!(
+            "cannot commit while a {} is in progress; finish it with git first",
+            operation(state)
+        )));
+    }
+
+    let signature = repo.signature()?;
+    let mut index = repo.index()?;
+    if index.has_conflicts() {
+        return Err(git2::Error::from_str("resolve conflicts before committing"));
+    }
+    let tree = repo.find_tree(index.write_tree()?)?;
+    let head = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+
+    if amend {
+        let head = head.ok_or_else(|| git2::Error::from_str("nothing to amend"))?;
+        // Keep the original author, like `git commit --amend`
+        return head.amend(
+            Some("HEAD"),
+            None,
+            Some(&signature),
+            None,
+            Some(&message),
+            Some(&tree),
+        );
+    }
+
+    if head.as_ref().is_some_and(|h| h.tree_id() == tree.id()) && state != RepositoryState::Merge {
+        return Err(git2::Error::from_str("nothing staged to commit"));
+    }
+
+    let mut parents: Vec<_> = head.into_iter().collect();
+    if state == RepositoryState::Merge {
+        // `mergehead_foreach` needs `&mut Repository` while the tree borrows it
+        let merge_heads = std::fs::read_to_string(repo.path().join("MERGE_HEAD"))
+            .map_err(|e| git2::Error::from_str(&format!("MERGE_HEAD: {e}")))?;
+        for line in merge_heads.lines().filter(|l| !l.is_empty()) {
+            parents.push(repo.find_commit(Oid::from_str(line.trim())?)?);
+        }
+    }
+    let parent_refs: Vec<_> = parents.iter().collect();
+
+    let oid = repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        &message,
+        &tree,
+        &parent_refs,
+    )?;
+    finish_state(&repo, state)?;
+    Ok(oid)
+}
+
+/// Name of the operation behind `state`, for refusing to commit during it.
+fn operation(state: RepositoryState) -> &'static str {
+    match state {
+        RepositoryState::Bisect => "bisect",
+        RepositoryState::CherryPickSequence => "multi-commit cherry-pick",
+        RepositoryState::RevertSequence => "multi-commit revert",
+        RepositoryState::ApplyMailbox | RepositoryState::ApplyMailboxOrRebase => "git am",
+        _ => "rebase",
+    }
+}
+
+/// Remove the files of the merge, cherry-pick or revert the new commit
+/// concludes. Unlike `Repository::cleanup_state`, this never touches a
+/// rebase, bisect or sequencer.
+fn finish_state(repo: &Repository, state: RepositoryState) -> Result<(), git2::Error> {
+    let files: &[&str] = match state {
+        RepositoryState::Merge => &["MERGE_HEAD", "MERGE_MODE", "MERGE_MSG"],
+        RepositoryState::CherryPick => &["CHERRY_PICK_HEAD", "MERGE_MSG"],
+        RepositoryState::Revert => &["REVERT_HEAD", "MERGE_MSG"],
+        _ => &[],
+    };
+    for file in files {
+        match std::fs::remove_file(repo.path().join(file)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(git2::Error::from_str(&format!("{file}: {e}")));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
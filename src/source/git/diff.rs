@@ -0,0 +1,366 @@
+use std::ops::Range;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use git2::{Delta, Diff, DiffOptions, Patch, Repository};
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::parsing::SyntaxSet;
+
+/// Diffs longer than this are cut off; the viewer says so.
+const MAX_LINES: usize = 20_000;
+/// Longer lines (minified files, lockfiles) are truncated.
+const MAX_LINE_LEN: usize = 1_000;
+/// Syntax highlighting is the slow part, so big diffs are shown uncolored.
+const MAX_HIGHLIGHT_LINES: usize = 5_000;
+
+type Rgb = (u8, u8, u8);
+/// Syntax colors of one line, as byte ranges.
+type LineColors = Vec<(Range<usize>, Rgb)>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffMode {
+    /// Index vs HEAD, what `git diff --cached` shows
+    Staged,
+    /// Worktree vs index, what `git diff` shows
+    Unstaged,
+}
+
+#[derive(Debug, Clone)]
+pub struct FileDiff {
+    /// Set instead of hunks when git considers either side binary.
+    pub binary: Option<BinaryInfo>,
+    pub hunks: Vec<Hunk>,
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct BinaryInfo {
+    pub old_size: u64,
+    pub new_size: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Hunk {
+    pub header: String,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind {
+    Context,
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Clone)]
+pub struct DiffLine {
+    pub kind: LineKind,
+    pub old_lineno: Option<u32>,
+    pub new_lineno: Option<u32>,
+    pub segments: Vec<Segment>,
+}
+
+/// A run of text with one syntax color. `emphasis` marks the part of a
+/// changed line that differs from its counterpart on the other side.
+#[derive(Debug, Clone)]
+pub struct Segment {
+    pub text: String,
+    pub rgb: Option<Rgb>,
+    pub emphasis: bool,
+}
+
+impl DiffMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DiffMode::Staged => "staged",
+            DiffMode::Unstaged => "unstaged",
+        }
+    }
+
+    pub fn toggle(self) -> Self {
+        match self {
+            DiffMode::Staged => DiffMode::Unstaged,
+            DiffMode::Unstaged => DiffMode::Staged,
+        }
+    }
+}
+
+impl FileDiff {
+    pub fn line_count(&self) -> usize {
+        self.hunks.iter().map(|h| h.lines.len()).sum()
+    }
+}
+
+fn highlighting() -> &'static (SyntaxSet, Theme) {
+    static HIGHLIGHTING: OnceLock<(SyntaxSet, Theme)> = OnceLock::new();
+    HIGHLIGHTING.get_or_init(|| {
+        let mut themes = ThemeSet::load_defaults();
+        let theme = themes
+            .themes
+            .remove("base16-ocean.dark")
+            .unwrap_or_default();
+        (SyntaxSet::load_defaults_newlines(), theme)
+    })
+}
+
+fn truncate(mut line: String) -> String {
+    if line.len() > MAX_LINE_LEN {
+        let mut end = MAX_LINE_LEN;
+        while !line.is_char_boundary(end) {
+            end -= 1;
+        }
+        line.truncate(end);
+        line.push('…');
+    }
+    line
+}
+
+/// Byte range where `a` and `b` differ once their common prefix and suffix
+/// are trimmed, for `a` and `b` respectively.
+fn changed_ranges(a: &str, b: &str) -> (Range<usize>, Range<usize>) {
+    let prefix = a
+        .char_indices()
+        .zip(b.chars())
+        .find(|((_, ca), cb)| ca != cb)
+        .map(|((i, _), _)| i)
+        .unwrap_or(a.len().min(b.len()));
+    let suffix = a[prefix..]
+        .chars()
+        .rev()
+        .zip(b[prefix..].chars().rev())
+        .take_while(|(ca, cb)| ca == cb)
+        .map(|(c, _)| c.len_utf8())
+        .sum::<usize>();
+    (prefix..a.len() - suffix, prefix..b.len() - suffix)
+}
+
+/// Pair each run of removed lines with the added lines that follow it and
+/// mark what changed within each pair. Unequal runs are paired line by line
+/// up to the shorter one, like `diff-highlight`.
+fn emphasis_ranges(lines: &[(LineKind, String)]) -> Vec<Option<Range<usize>>> {
+    let mut ranges = vec![None; lines.len()];
+    let mut i = 0;
+    while i < lines.len() {
+        if lines[i].0 != LineKind::Removed {
+            i += 1;
+            continue;
+        }
+        let removed_start = i;
+        while i < lines.len() && lines[i].0 == LineKind::Removed {
+            i += 1;
+        }
+        let added_start = i;
+        while i < lines.len() && lines[i].0 == LineKind::Added {
+            i += 1;
+        }
+        let pairs = (added_start - removed_start).min(i - added_start);
+        for k in 0..pairs {
+            let (old, new) = (removed_start + k, added_start + k);
+            let (old_range, new_range) = changed_ranges(&lines[old].1, &lines[new].1);
+            // A line that changed entirely gains nothing from emphasis
+            if old_range.len() < lines[old].1.len() || new_range.len() < lines[new].1.len() {
+                ranges[old] = Some(old_range);
+                ranges[new] = Some(new_range);
+            }
+        }
+    }
+    ranges
+}
+
+/// Split `text` into segments at both syntax-color and emphasis boundaries.
+fn build_segments(
+    text: &str,
+    colors: &[(Range<usize>, Rgb)],
+    emphasis: Option<&Range<usize>>,
+) -> Vec<Segment> {
+    let mut cuts = vec![0, text.len()];
+    cuts.extend(colors.iter().flat_map(|(r, _)| [r.start, r.end]));
+    if let Some(e) = emphasis {
+        cuts.extend([e.start, e.end]);
+    }
+    cuts.sort_unstable();
+    cuts.dedup();
+
+    cuts.windows(2)
+        .filter(|w| w[0] < w[1] && w[1] <= text.len())
+        .map(|w| {
+            let rgb = colors
+                .iter()
+                .find(|(r, _)| r.start <= w[0] && w[0] < r.end)
+                .map(|(_, rgb)| *rgb);
+            Segment {
+                text: text[w[0]..w[1]].to_string(),
+                rgb,
+                emphasis: emphasis.is_some_and(|e| e.start <= w[0] && w[1] <= e.end),
+            }
+        })
+        .collect()
+}
+
+/// Syntax colors per line. Each hunk restarts the highlighter since it
+/// begins mid-file.
+fn highlight(path: &str, hunks: &[Vec<(LineKind, String)>]) -> Vec<Vec<LineColors>> {
+    let total: usize = hunks.iter().map(Vec::len).sum();
+    let (syntaxes, theme) = highlighting();
+    let syntax = Path::new(path)
+        .extension()
+        .and_then(|ext| syntaxes.find_syntax_by_extension(&ext.to_string_lossy()))
+        .or_else(|| {
+            let first = hunks.first()?.first()?;
+            syntaxes.find_syntax_by_first_line(&first.1)
+        });
+    let Some(syntax) = syntax.filter(|_| total <= MAX_HIGHLIGHT_LINES) else {
+        return hunks.iter().map(|h| vec![Vec::new(); h.len()]).collect();
+    };
+
+    hunks
+        .iter()
+        .map(|hunk| {
+            let mut highlighter = HighlightLines::new(syntax, theme);
+            hunk.iter()
+                .map(|(_, text)| {
+                    let line = format!("{text}\n");
+                    let Ok(styled) = highlighter.highlight_line(&line, syntaxes) else {
+                        return Vec::new();
+                    };
+                    let mut offset = 0;
+                    styled
+                        .into_iter()
+                        .map(|(style, piece)| {
+                            let range = offset..(offset + piece.len()).min(text.len());
+                            offset += piece.len();
+                            let fg = style.foreground;
+                            (range, (fg.r, fg.g, fg.b))
+                        })
+                        .filter(|(range, _)| !range.is_empty())
+                        .collect()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// The git2 diff behind `diff_file`, also used to pick hunks when staging.
+/// `reverse` swaps the sides, turning it into the patch that undoes the change.
+pub(super) fn raw_diff<'r>(
+    repo: &'r Repository,
+    path: &str,
+    mode: DiffMode,
+    reverse: bool,
+) -> std::result::Result<Diff<'r>, git2::Error> {
+    let mut opts = DiffOptions::new();
+    opts.pathspec(path)
+        .disable_pathspec_match(true)
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .show_untracked_content(true)
+        .reverse(reverse);
+
+    match mode {
+        DiffMode::Staged => {
+            // Unborn HEAD: everything in the index is new
+            let head = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+            repo.diff_tree_to_index(head.as_ref(), None, Some(&mut opts))
+        }
+        DiffMode::Unstaged => repo.diff_index_to_workdir(None, Some(&mut opts)),
+    }
+}
+
+/// Diff of a single file, in either mode. Untracked files show as entirely
+/// added in `Unstaged` mode.
+pub fn diff_file(
+    repo_path: &Path,
+    path: &str,
+    mode: DiffMode,
+) -> std::result::Result<FileDiff, git2::Error> {
+    let repo = Repository::open(repo_path)?;
+    let diff = raw_diff(&repo, path, mode, false)?;
+
+    let mut result = FileDiff {
+        binary: None,
+        hunks: Vec::new(),
+        truncated: false,
+    };
+    let Some(delta) = diff.deltas().next() else {
+        return Ok(result);
+    };
+    // Conflicted entries have no single index side to diff against
+    if delta.status() == Delta::Conflicted && mode == DiffMode::Staged {
+        return Ok(result);
+    }
+
+    let Some(patch) = Patch::from_diff(&diff, 0)? else {
+        return Ok(result);
+    };
+    let delta = patch.delta();
+    if delta.flags().is_binary() {
+        result.binary = Some(BinaryInfo {
+            old_size: delta.old_file().size(),
+            new_size: delta.new_file().size(),
+        });
+        return Ok(result);
+    }
+
+    let mut raw_hunks = Vec::new();
+    let mut line_numbers = Vec::new();
+    let mut budget = MAX_LINES;
+    for h in 0..patch.num_hunks() {
+        if budget == 0 {
+            result.truncated = true;
+            break;
+        }
+        let (hunk, count) = patch.hunk(h)?;
+        let header = String::from_utf8_lossy(hunk.header())
+            .trim_end()
+            .to_string();
+        let mut lines = Vec::new();
+        let mut numbers = Vec::new();
+        for l in 0..count {
+            if budget == 0 {
+                result.truncated = true;
+                break;
+            }
+            let line = patch.line_in_hunk(h, l)?;
+            let kind = match line.origin() {
+                ' ' => LineKind::Context,
+                '+' => LineKind::Added,
+                '-' => LineKind::Removed,
+                // "\ No newline at end of file" and friends
+                _ => continue,
+            };
+            let text = String::from_utf8_lossy(line.content());
+            let text = text.trim_end_matches(['\n', '\r']).replace('\t', "    ");
+            lines.push((kind, truncate(text)));
+            numbers.push((line.old_lineno(), line.new_lineno()));
+            budget -= 1;
+        }
+        raw_hunks.push((header, lines));
+        line_numbers.push(numbers);
+    }
+
+    let texts: Vec<Vec<(LineKind, String)>> =
+        raw_hunks.iter().map(|(_, lines)| lines.clone()).collect();
+    let colors = highlight(path, &texts);
+
+    for (((header, lines), numbers), colors) in raw_hunks.into_iter().zip(line_numbers).zip(colors)
+    {
+        let emphasis = emphasis_ranges(&lines);
+        let lines = lines
+            .into_iter()
+            .zip(numbers)
+            .zip(colors)
+            .zip(emphasis)
+            .map(
+                |((((kind, text), (old, new)), colors), emphasis)| DiffLine {
+                    kind,
+                    old_lineno: old,
+                    new_lineno: new,
+                    segments: build_segments(&text, &colors, emphasis.as_ref()),
+                },
+            )
+            .collect();
+        result.hunks.push(Hunk { header, lines });
+    }
+    Ok(result)
+}
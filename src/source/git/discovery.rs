@@ -0,0 +1,72 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use globset::GlobSet;
+
+/// Options for finding repositories under `scan_roots`.
+#[derive(Debug, Clone)]
+pub struct DiscoveryOptions {
+    pub roots: Vec<PathBuf>,
+    pub max_depth: usize,
+    pub ignore: GlobSet,
+    pub follow_symlinks: bool,
+}
+
+/// Walk every root looking for directories that contain a `.git` entry: a
+/// directory for regular clones, a file for linked worktrees. The walk does
+/// not descend into a repository once found.
+pub fn discover_repos(options: &DiscoveryOptions) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let mut visited = HashSet::new();
+    for root in &options.roots {
+        walk(root, 0, options, &mut visited, &mut found);
+    }
+    found.sort();
+    found
+}
+
+fn walk(
+    dir: &Path,
+    depth: usize,
+    options: &DiscoveryOptions,
+    visited: &mut HashSet<PathBuf>,
+    found: &mut Vec<PathBuf>,
+) {
+    // Guard against symlink loops and roots that overlap
+    let Ok(canonical) = fs::canonicalize(dir) else {
+        return;
+    };
+    if !visited.insert(canonical) {
+        return;
+    }
+
+    if dir.join(".git").exists() {
+        found.push(dir.to_path_buf());
+        return;
+    }
+    if depth >= options.max_depth {
+        return;
+    }
+
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if options.ignore.is_match(&path) {
+            continue;
+        }
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let is_dir = if file_type.is_symlink() {
+            options.follow_symlinks && path.is_dir()
+        } else {
+            file_type.is_dir()
+        };
+        if is_dir {
+            walk(&path, depth + 1, options, visited, found);
+        }
+    }
+}
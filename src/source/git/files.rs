@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use git2::{DiffDelta, DiffFile, Repository, Status};
+
+use crate::config::StatusConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FileSection {
+    Conflicted,
+    Staged,
+    Unstaged,
+    Untracked,
+}
+
+/// One line of the drill-down, like a line of `git status`. A file that is
+/// both staged and modified again appears once in each section.
+#[derive(Debug, Clone)]
+pub struct FileChange {
+    pub path: String,
+    pub section: FileSection,
+    /// Porcelain-style status letter: M, A, D, R, T, ? or U.
+    pub code: char,
+    pub renamed_from: Option<String>,
+    pub conflict: Option<ConflictKind>,
+    /// `<<<<<<<` lines still present in the worktree file.
+    pub markers: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictKind {
+    BothModified,
+    BothAdded,
+    BothDeleted,
+    AddedByUs,
+    AddedByThem,
+    DeletedByUs,
+    DeletedByThem,
+}
+
+impl FileSection {
+    pub fn title(&self) -> &'static str {
+        match self {
+            FileSection::Conflicted => "Conflicted",
+            FileSection::Staged => "Staged",
+            FileSection::Unstaged => "Unstaged",
+            FileSection::Untracked => "Untracked",
+        }
+    }
+}
+
+impl ConflictKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConflictKind::BothModified => "both modified",
+            ConflictKind::BothAdded => "both added",
+            ConflictKind::BothDeleted => "both deleted",
+            ConflictKind::AddedByUs => "added by us",
+            ConflictKind::AddedByThem => "added by them",
+            ConflictKind::DeletedByUs => "deleted by us",
+            ConflictKind::DeletedByThem => "deleted by them",
+        }
+    }
+
+    fn from_sides(ancestor: bool, ours: bool, theirs: bool) -> Self {
+        match (ancestor, ours, theirs) {
+            (true, true, true) => ConflictKind::BothModified,
+            (false, true, true) => ConflictKind::BothAdded,
+            (true, false, false) => ConflictKind::BothDeleted,
+            (false, true, false) => ConflictKind::AddedByUs,
+            (false, false, true) => ConflictKind::AddedByThem,
+            (true, false, true) => ConflictKind::DeletedByUs,
+            (true, true, false) => ConflictKind::DeletedByThem,
+            (false, false, false) => ConflictKind::BothModified,
+        }
+    }
+}
+
+fn index_code(s: Status) -> Option<char> {
+    if s.contains(Status::INDEX_NEW) {
+        Some('A')
+    } else if s.contains(Status::INDEX_MODIFIED) {
+        Some('M')
+    } else if s.contains(Status::INDEX_DELETED) {
+        Some('D')
+    } else if s.contains(Status::INDEX_RENAMED) {
+        Some('R')
+    } else if s.contains(Status::INDEX_TYPECHANGE) {
+        Some('T')
+    } else {
+        None
+    }
+}
+
+fn worktree_code(s: Status) -> Option<char> {
+    if s.contains(Status::WT_MODIFIED) {
+        Some('M')
+    } else if s.contains(Status::WT_DELETED) {
+        Some('D')
+    } else if s.contains(Status::WT_RENAMED) {
+        Some('R')
+    } else if s.contains(Status::WT_TYPECHANGE) {
+        Some('T')
+    } else {
+        None
+    }
+}
+
+/// New path of a delta, plus the old one if it was renamed. `StatusEntry::path`
+/// alone reports the pre-rename path.
+fn delta_paths(delta: Option<DiffDelta>, fallback: &str) -> (String, Option<String>) {
+    let Some(delta) = delta else {
+        return (fallback.to_string(), None);
+    };
+    let path_of = |f: DiffFile| f.path().map(|p| p.to_string_lossy().to_string());
+    let new = path_of(delta.new_file()).unwrap_or_else(|| fallback.to_string());
+    let old = path_of(delta.old_file()).filter(|old| *old != new);
+    (new, old)
+}
+
+fn count_markers(path: &Path) -> usize {
+    std::fs::read(path)
+        .map(|content| {
+            content
+                .split(|&b| b == b'\n')
+                .filter(|line| line.starts_with(b"<<<<<<< "))
+                .count()
+        })
+        .unwrap_or(0)
+}
+
+/// Every changed file in the repository that its status options let
+/// through, sorted by section then path.
+pub fn collect_files(
+    path: &Path,
+    config: &StatusConfig,
+) -> std::result::Result<Vec<FileChange>, git2::Error> {
+    let repo = Repository::open(path)?;
+
+    let mut conflicts: HashMap<String, ConflictKind> = HashMap::new();
+    let index = repo.index()?;
+    if index.has_conflicts() {
+        for conflict in index.conflicts()? {
+            let conflict = conflict?;
+            let entry = conflict
+                .our
+                .as_ref()
+                .or(conflict.their.as_ref())
+                .or(conflict.ancestor.as_ref());
+            if let Some(entry) = entry {
+                conflicts.insert(
+                    String::from_utf8_lossy(&entry.path).to_string(),
+                    ConflictKind::from_sides(
+                        conflict.ancestor.is_some(),
+                        conflict.our.is_some(),
+                        conflict.their.is_some(),
+                    ),
+                );
+            }
+        }
+    }
+
+    let mut opts = super::status_options(config);
+    opts.include_ignored(false)
+        .renames_head_to_index(true)
+        .renames_index_to_workdir(true);
+    let statuses = repo.statuses(Some(&mut opts))?;
+    let workdir = repo.workdir().unwrap_or(path);
+
+    let mut changes = Vec::new();
+    for entry in statuses.iter() {
+        let s = entry.status();
+        let Some(file) = entry.path().map(str::to_string) else {
+            continue;
+        };
+
+        if s.contains(Status::CONFLICTED) {
+            changes.push(FileChange {
+                markers: count_markers(&workdir.join(&file)),
+                conflict: Some(
+                    conflicts
+                        .get(&file)
+                        .copied()
+                        .unwrap_or(ConflictKind::BothModified),
+                ),
+                path: file,
+                section: FileSection::Conflicted,
+                code: 'U',
+                renamed_from: None,
+            });
+            continue;
+        }
+
+        if let Some(code) = index_code(s) {
+            let (path, renamed_from) = delta_paths(entry.head_to_index(), &file);
+            changes.push(FileChange {
+                path,
+                section: FileSection::Staged,
+                code,
+                renamed_from,
+                conflict: None,
+                markers: 0,
+            });
+        }
+        if let Some(code) = worktree_code(s) {
+            let (path, renamed_from) = delta_paths(entry.index_to_workdir(), &file);
+            changes.push(FileChange {
+                path,
+                section: FileSection::Unstaged,
+                code,
+                renamed_from,
+                conflict: None,
+                markers: 0,
+            });
+        }
+        if s.contains(Status::WT_NEW) {
+            changes.push(FileChange {
+                path: file,
+                section: FileSection::Untracked,
+                code: '?',
+                renamed_from: None,
+                conflict: None,
+                markers: 0,
+            });
+        }
+    }
+
+    changes.sort_by(|a, b| a.section.cmp(&b.section).then_with(|| a.path.cmp(&b.path)));
+    Ok(changes)
+}
@@ -0,0 +1,218 @@
+use std::path::Path;
+
+use git2::build::CheckoutBuilder;
+use git2::{
+    ApplyLocation, ApplyOptions, DiffFindOptions, Patch, Repository, Status, StatusOptions,
+};
+
+use super::diff::{raw_diff, DiffMode};
+
+/// A change to the index or worktree requested from the files or diff view.
+/// Hunks are numbered in the order the diff viewer shows them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexOp {
+    StageFile(String),
+    UnstageFile(String),
+    /// Drop worktree changes; untracked files are deleted and unstaged
+    /// renames undone
+    DiscardFile(String),
+    StageHunk {
+        path: String,
+        hunk: usize,
+    },
+    UnstageHunk {
+        path: String,
+        hunk: usize,
+    },
+    DiscardHunk {
+        path: String,
+        hunk: usize,
+    },
+}
+
+impl IndexOp {
+    /// Discards can't be undone, so the UI asks first.
+    pub fn is_destructive(&self) -> bool {
+        matches!(self, IndexOp::DiscardFile(_) | IndexOp::DiscardHunk { .. })
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            IndexOp::StageFile(path) => format!("stage {path}"),
+            IndexOp::UnstageFile(path) => format!("unstage {path}"),
+            IndexOp::DiscardFile(path) => format!("discard changes to {path}"),
+            IndexOp::StageHunk { path, hunk } => format!("stage hunk {} of {path}", hunk + 1),
+            IndexOp::UnstageHunk { path, hunk } => {
+                format!("unstage hunk {} of {path}", hunk + 1)
+            }
+            IndexOp::DiscardHunk { path, hunk } => {
+                format!("discard hunk {} of {path}", hunk + 1)
+            }
+        }
+    }
+}
+
+fn stage_file(repo: &Repository, path: &str) -> Result<(), git2::Error> {
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| git2::Error::from_str("bare repository has no worktree"))?;
+    let mut index = repo.index()?;
+    // Adding also resolves a conflict; a file deleted in the worktree is
+    // staged as a removal
+    if workdir.join(path).symlink_metadata().is_ok() {
+        index.add_path(Path::new(path))?;
+    } else {
+        index.remove_path(Path::new(path))?;
+    }
+    index.write()
+}
+
+/// Where `path` was renamed from, if the index stages it as a rename of a
+/// file in `head`.
+fn rename_source(
+    repo: &Repository,
+    head: &git2::Commit,
+    path: &str,
+) -> Result<Option<String>, git2::Error> {
+    let mut diff = repo.diff_tree_to_index(Some(&head.tree()?), None, None)?;
+    diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+    Ok(diff.deltas().find_map(|delta| {
+        let renamed = delta.status() == git2::Delta::Renamed
+            && delta.new_file().path() == Some(Path::new(path));
+        renamed
+            .then(|| delta.old_file().path())
+            .flatten()
+            .map(|p| p.to_string_lossy().to_string())
+    }))
+}
+
+fn unstage_file(repo: &Repository, path: &str) -> Result<(), git2::Error> {
+    match repo.head().ok().and_then(|h| h.peel_to_commit().ok()) {
+        Some(head) => {
+            // A staged rename is undone on both sides, or the old path would
+            // stay staged as deleted
+            let mut paths = vec![path.to_string()];
+            paths.extend(rename_source(repo, &head, path)?);
+            repo.reset_default(Some(head.as_object()), paths)
+        }
+        // Nothing committed yet, so unstaging means dropping from the index
+        None => {
+            let mut index = repo.index()?;
+            index.remove_path(Path::new(path))?;
+            index.write()
+        }
+    }
+}
+
+/// Where `path` was renamed from in the worktree, if status pairs it with a
+/// file missing from the worktree the way the files view does.
+fn workdir_rename_source(repo: &Repository, path: &str) -> Result<Option<String>, git2::Error> {
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .renames_index_to_workdir(true);
+    let statuses = repo.statuses(Some(&mut opts))?;
+    Ok(statuses
+        .iter()
+        .filter(|entry| entry.status().contains(Status::WT_RENAMED))
+        .find_map(|entry| {
+            let delta = entry.index_to_workdir()?;
+            if delta.new_file().path() != Some(Path::new(path)) {
+                return None;
+            }
+            delta
+                .old_file()
+                .path()
+                .map(|p| p.to_string_lossy().to_string())
+        }))
+}
+
+fn checkout_from_index(repo: &Repository, path: &str) -> Result<(), git2::Error> {
+    let mut checkout = CheckoutBuilder::new();
+    checkout.force().path(path);
+    repo.checkout_index(None, Some(&mut checkout))
+}
+
+fn discard_file(repo: &Repository, path: &str) -> Result<(), git2::Error> {
+    if !repo.status_file(Path::new(path))?.contains(Status::WT_NEW) {
+        return checkout_from_index(repo, path);
+    }
+
+    // An unstaged rename is listed under its new path; undoing it also
+    // brings back the old one
+    let source = workdir_rename_source(repo, path)?;
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| git2::Error::from_str("bare repository has no worktree"))?;
+    let full = workdir.join(path);
+    let removed = if full.is_dir() {
+        std::fs::remove_dir_all(&full)
+    } else {
+        std::fs::remove_file(&full)
+    };
+    removed.map_err(|e| git2::Error::from_str(&format!("{path}: {e}")))?;
+    match source {
+        Some(source) => checkout_from_index(repo, &source),
+        None => Ok(()),
+    }
+}
+
+/// Apply only the `hunk`-th hunk of the file's diff in `mode`.
+fn apply_hunk(
+    repo: &Repository,
+    path: &str,
+    hunk: usize,
+    mode: DiffMode,
+    reverse: bool,
+    location: ApplyLocation,
+) -> Result<(), git2::Error> {
+    let diff = raw_diff(repo, path, mode, reverse)?;
+    let hunks = Patch::from_diff(&diff, 0)?.map_or(0, |p| p.num_hunks());
+    if hunk >= hunks {
+        return Err(git2::Error::from_str(
+            "hunk no longer exists; the file changed since the diff was loaded",
+        ));
+    }
+
+    let mut seen = 0;
+    let mut opts = ApplyOptions::new();
+    opts.hunk_callback(move |_| {
+        seen += 1;
+        seen - 1 == hunk
+    });
+    repo.apply(&diff, location, Some(&mut opts))
+}
+
+/// Blocking; run through `GitRequest::Index`.
+pub fn apply(repo_path: &Path, op: &IndexOp) -> Result<(), git2::Error> {
+    let repo = Repository::open(repo_path)?;
+    match op {
+        IndexOp::StageFile(path) => stage_file(&repo, path),
+        IndexOp::UnstageFile(path) => unstage_file(&repo, path),
+        IndexOp::DiscardFile(path) => discard_file(&repo, path),
+        IndexOp::StageHunk { path, hunk } => apply_hunk(
+            &repo,
+            path,
+            *hunk,
+            DiffMode::Unstaged,
+            false,
+            ApplyLocation::Index,
+        ),
+        IndexOp::UnstageHunk { path, hunk } => apply_hunk(
+            &repo,
+            path,
+            *hunk,
+            DiffMode::Staged,
+            true,
+            ApplyLocation::Index,
+        ),
+        IndexOp::DiscardHunk { path, hunk } => apply_hunk(
+            &repo,
+            path,
+            *hunk,
+            DiffMode::Unstaged,
+            true,
+            ApplyLocation::WorkDir,
+        ),
+    }
+}
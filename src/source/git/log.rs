@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use git2::{BranchType, Oid, Repository, Sort};
+
+/// A search reads at most this many commits past the page before giving up
+/// for now; searching again carries on from there.
+const MAX_SEARCH_SCAN: usize = 10_000;
+
+#[derive(Debug, Clone)]
+pub struct LogPage {
+    pub commits: Vec<LogCommit>,
+    /// More history exists past this page
+    pub more: bool,
+    /// Branch and tag names by commit; read with the first page only, later
+    /// pages are labeled from it
+    pub labels: Option<HashMap<Oid, Vec<RefLabel>>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LogCommit {
+    pub id: Oid,
+    pub parents: Vec<Oid>,
+    pub author: String,
+    /// Seconds since the epoch
+    pub time: i64,
+    pub summary: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RefLabel {
+    /// The checked-out branch, or `HEAD` when detached
+    Head(String),
+    Branch(String),
+    Remote(String),
+    Tag(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct CommitDetail {
+    pub id: Oid,
+    pub author: String,
+    pub committer: String,
+    pub time: i64,
+    pub message: String,
+    pub files: Vec<FileStat>,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct FileStat {
+    pub path: String,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+impl RefLabel {
+    pub fn name(&self) -> &str {
+        match self {
+            RefLabel::Head(name)
+            | RefLabel::Branch(name)
+            | RefLabel::Remote(name)
+            | RefLabel::Tag(name) => name,
+        }
+    }
+}
+
+/// Every branch and tag name, by the commit it points at.
+fn ref_labels(repo: &Repository) -> Result<HashMap<Oid, Vec<RefLabel>>, git2::Error> {
+    let mut labels: HashMap<Oid, Vec<RefLabel>> = HashMap::new();
+
+    let head = repo.head().ok();
+    let head_branch = head
+        .as_ref()
+        .filter(|h| h.is_branch())
+        .and_then(|h| h.shorthand().map(str::to_string));
+    if let Some(target) = head.as_ref().and_then(|h| h.target()) {
+        if head_branch.is_none() {
+            labels
+                .entry(target)
+                .or_default()
+                .push(RefLabel::Head("HEAD".to_string()));
+        }
+    }
+
+    for branch in repo.branches(None)? {
+        let (branch, kind) = branch?;
+        let (Some(name), Some(target)) = (branch.name()?, branch.get().target()) else {
+            continue;
+        };
+        let label = match kind {
+            BranchType::Local if Some(name) == head_branch.as_deref() => {
+                RefLabel::Head(name.to_string())
+            }
+            BranchType::Local => RefLabel::Branch(name.to_string()),
+            // origin/HEAD just repeats the default branch
+            BranchType::Remote if name.ends_with("/HEAD") => continue,
+            BranchType::Remote => RefLabel::Remote(name.to_string()),
+        };
+        labels.entry(target).or_default().push(label);
+    }
+
+    repo.tag_foreach(|oid, name| {
+        let name = String::from_utf8_lossy(name);
+        let name = name.trim_start_matches("refs/tags/").to_string();
+        // Annotated tags point at a tag object; label the commit instead
+        let target = repo.find_tag(oid).map(|t| t.target_id()).unwrap_or(oid);
+        labels.entry(target).or_default().push(RefLabel::Tag(name));
+        true
+    })?;
+
+    for refs in labels.values_mut() {
+        refs.sort_by_key(|r| !matches!(r, RefLabel::Head(_)));
+    }
+    Ok(labels)
+}
+
+impl LogCommit {
+    /// Case-insensitive match of an already lowercased `query` against the
+    /// subject and author.
+    pub fn matches(&self, query: &str) -> bool {
+        self.summary.to_lowercase().contains(query) || self.author.to_lowercase().contains(query)
+    }
+}
+
+/// `limit` commits starting `skip` commits in, newest first, from HEAD and
+/// every local branch so side branches show in the graph. With `find`, the
+/// page runs past `limit` until it includes a commit matching it, or
+/// `MAX_SEARCH_SCAN` more commits have been read.
+pub fn read_log(
+    path: &Path,
+    skip: usize,
+    limit: usize,
+    find: Option<&str>,
+) -> Result<LogPage, git2::Error> {
+    let repo = Repository::open(path)?;
+    let mut walk = repo.revwalk()?;
+    // Time order alone streams from the tips; adding topological order would
+    // walk the whole history before the first commit comes out
+    walk.set_sorting(Sort::TIME)?;
+    // An unborn HEAD has nothing to walk
+    if repo.head().is_ok() {
+        walk.push_head()?;
+    }
+    walk.push_glob("refs/heads/*")?;
+
+    let labels = if skip == 0 {
+        Some(ref_labels(&repo)?)
+    } else {
+        None
+    };
+    let mut commits = Vec::with_capacity(limit);
+    let find = find.map(str::to_lowercase);
+    let mut found = find.is_none();
+    let mut ids = walk.skip(skip).peekable();
+    while commits.len() < limit || (!found && commits.len() < limit + MAX_SEARCH_SCAN) {
+        let Some(id) = ids.next() else {
+            break;
+        };
+        let commit = repo.find_commit(id?)?;
+        let commit = LogCommit {
+            id: commit.id(),
+            parents: commit.parent_ids().collect(),
+            author: commit.author().name().unwrap_or_default().to_string(),
+            time: commit.time().seconds(),
+            summary: commit.summary().unwrap_or_default().to_string(),
+        };
+        found |= find.as_deref().is_some_and(|q| commit.matches(q));
+        commits.push(commit);
+    }
+
+    Ok(LogPage {
+        more: ids.peek().is_some(),
+        commits,
+        labels,
+    })
+}
+
+/// Full message and diffstat against the first parent.
+pub fn commit_detail(path: &Path, id: Oid) -> Result<CommitDetail, git2::Error> {
+    let repo = Repository::open(path)?;
+    let commit = repo.find_commit(id)?;
+    let parent_tree = match commit.parents().next() {
+        Some(parent) => Some(parent.tree()?),
+        None => None,
+    };
+    let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+
+    let mut files = Vec::new();
+    for i in 0..diff.deltas().len() {
+        let Some(patch) = git2::Patch::from_diff(&diff, i)? else {
+            continue;
+        };
+        let (_, insertions, deletions) = patch.line_stats()?;
+        let delta = patch.delta();
+        let path = delta
+            .new_file()
+            .path()
+            .or(delta.old_file().path())
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default();
+        files.push(FileStat {
+            path,
+            insertions,
+            deletions,
+        });
+    }
+    let stats = diff.stats()?;
+
+    let signature = |s: git2::Signature| {
+        format!(
+            "{} <{}>",
+            s.name().unwrap_or_default(),
+            s.email().unwrap_or_default()
+        )
+    };
+    Ok(CommitDetail {
+        id,
+        author: signature(commit.author()),
+        committer: signature(commit.committer()),
+        time: commit.time().seconds(),
+        message: commit.message().unwrap_or_default().to_string(),
+        files,
+        insertions: stats.insertions(),
+        deletions: stats.deletions(),
+    })
+}
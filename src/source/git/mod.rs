@@ -0,0 +1,1147 @@
+mod auto_fetch;
+pub mod branches;
+pub mod commit;
+pub mod diff;
+pub mod discovery;
+pub mod files;
+pub mod index;
+pub mod log;
+mod reader;
+pub mod remote;
+pub mod stale;
+pub mod stash;
+pub mod submodules;
+mod watch;
+pub mod worktrees;
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use auto_fetch::AutoFetch;
+use git2::Repository;
+use tokio::sync::mpsc;
+
+use super::{build_globset, DataSnapshot, DataSource};
+use crate::config::{GitConfig, StatusConfig};
+use crate::errors::Result;
+use branches::{BranchList, BranchOp};
+use commit::CommitDraft;
+use diff::{DiffMode, FileDiff};
+use discovery::DiscoveryOptions;
+use files::FileChange;
+use index::IndexOp;
+use log::{CommitDetail, LogPage};
+use reader::{Read, StatusReader};
+use remote::{RemoteOp, TransferProgress};
+use stale::{BranchRef, Deleted, StaleReport};
+use stash::{StashEntry, StashOp, StashPreview};
+use submodules::{SubmoduleInfo, SubmoduleOp};
+use watch::RepoWatcher;
+use worktrees::{WorktreeInfo, WorktreeOp};
+
+#[derive(Debug, Clone, Default)]
+pub struct GitSnapshot {
+    pub repos: Vec<RepoEntry>,
+    /// Set when `scan_ignore` didn't compile; scanning then ignores nothing
+    pub scan_error: Option<String>,
+}
+
+/// One row per configured repository, whether or not collection succeeded.
+#[derive(Debug, Clone)]
+pub struct RepoEntry {
+    pub path: PathBuf,
+    pub name: String,
+    /// `None` until the repo has been read once
+    pub status: Option<std::result::Result<RepoStatus, RepoError>>,
+    /// A read of the repo is still running
+    pub scanning: bool,
+    /// Why the last background fetch failed, until one succeeds
+    pub fetch_error: Option<String>,
+}
+
+/// A `git2::Error` flattened into something `Clone` for the UI.
+#[derive(Debug, Clone)]
+pub struct RepoError {
+    pub message: String,
+    pub class: git2::ErrorClass,
+    pub code: git2::ErrorCode,
+    pub raw_code: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct RepoStatus {
+    pub head: HeadState,
+    pub state: RepoState,
+    /// Paths with unresolved merge conflicts
+    pub conflicts: usize,
+    pub upstream: Upstream,
+    pub modified: usize,
+    pub staged: usize,
+    pub untracked: usize,
+    pub stashes: usize,
+    /// Only counted when the repo's status options include ignored files
+    pub ignored: Option<usize>,
+    /// Seconds since the epoch, from when `FETCH_HEAD` was last written
+    pub last_fetched: Option<i64>,
+    /// HEAD's commit; `None` on an unborn branch
+    pub last_commit: Option<LastCommit>,
+    /// Seconds since the epoch of the latest local activity: staging, a
+    /// commit or checkout, or an edit to a changed file
+    pub last_change: Option<i64>,
+    /// Linked worktrees; empty for a repo that is itself a linked worktree
+    pub worktrees: Vec<WorktreeInfo>,
+    pub submodules: Vec<SubmoduleInfo>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LastCommit {
+    /// Short SHA
+    pub id: String,
+    pub author: String,
+    /// Seconds since the epoch
+    pub time: i64,
+    pub subject: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeadState {
+    Branch(String),
+    /// Short SHA of the detached commit
+    Detached(String),
+    /// Branch with no commits yet
+    Unborn(String),
+}
+
+/// An operation left in progress, as reported by `git2::Repository::state`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RepoState {
+    Clean,
+    Merge,
+    Rebase {
+        interactive: bool,
+        /// `(current, total)` step, when git recorded it
+        step: Option<(usize, usize)>,
+    },
+    CherryPick,
+    Revert,
+    Bisect,
+    /// `git am` applying a patch series
+    ApplyMailbox,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Upstream {
+    /// No upstream configured (or HEAD is not a branch)
+    None,
+    Tracking {
+        name: String,
+        ahead: usize,
+        behind: usize,
+    },
+    /// Upstream is configured but its ref no longer exists
+    Gone { name: String },
+}
+
+/// On-demand git work requested from the UI, run off the UI thread.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GitRequest {
+    Files(PathBuf),
+    Diff {
+        repo: PathBuf,
+        path: String,
+        mode: DiffMode,
+    },
+    /// Change the index or worktree, then re-run `reload` so open views
+    /// reflect the result without waiting for the next poll.
+    Index {
+        repo: PathBuf,
+        op: IndexOp,
+        reload: Vec<GitRequest>,
+    },
+    PrepareCommit(PathBuf),
+    /// Commit (or amend) with `message`, then re-run `reload` like `Index`.
+    Commit {
+        repo: PathBuf,
+        message: String,
+        amend: bool,
+        reload: Vec<GitRequest>,
+    },
+    Log {
+        repo: PathBuf,
+        skip: usize,
+        limit: usize,
+        /// Keep reading past `limit` until a subject or author matches
+        find: Option<String>,
+    },
+    CommitDetail {
+        repo: PathBuf,
+        id: git2::Oid,
+    },
+    Branches(PathBuf),
+    /// Change branches, then re-run `reload` like `Index`.
+    Branch {
+        repo: PathBuf,
+        op: BranchOp,
+        reload: Vec<GitRequest>,
+    },
+    Stashes(PathBuf),
+    StashPreview {
+        repo: PathBuf,
+        id: git2::Oid,
+    },
+    /// Apply, pop or drop a stash, then re-run `reload` like `Index`.
+    Stash {
+        repo: PathBuf,
+        op: StashOp,
+        reload: Vec<GitRequest>,
+    },
+    Worktrees(PathBuf),
+    /// Add or prune a worktree, then re-run `reload` like `Index`.
+    Worktree {
+        repo: PathBuf,
+        op: WorktreeOp,
+        reload: Vec<GitRequest>,
+    },
+    Submodules(PathBuf),
+    /// Init and update submodules, then re-run `reload` like `Index`.
+    Submodule {
+        repo: PathBuf,
+        op: SubmoduleOp,
+        reload: Vec<GitRequest>,
+    },
+    /// Stale local branches across these repos
+    StaleBranches(Vec<PathBuf>),
+    /// Delete branches that haven't moved since they were listed, then
+    /// re-run `reload` like `Index`.
+    DeleteBranches {
+        branches: Vec<BranchRef>,
+        reload: Vec<GitRequest>,
+    },
+}
+
+/// Result of a `GitRequest`, delivered as `DataSnapshot::GitEvent`.
+#[derive(Debug, Clone)]
+pub enum GitEvent {
+    Files {
+        repo: PathBuf,
+        result: std::result::Result<Vec<FileChange>, RepoError>,
+    },
+    Diff {
+        repo: PathBuf,
+        path: String,
+        mode: DiffMode,
+        result: std::result::Result<FileDiff, RepoError>,
+    },
+    Index {
+        repo: PathBuf,
+        op: IndexOp,
+        result: std::result::Result<(), RepoError>,
+        /// Fresh table row for the repo
+        entry: Box<RepoEntry>,
+        reloaded: Vec<GitEvent>,
+    },
+    CommitDraft {
+        repo: PathBuf,
+        result: std::result::Result<CommitDraft, RepoError>,
+    },
+    Committed {
+        repo: PathBuf,
+        /// Short id of the new commit
+        result: std::result::Result<String, RepoError>,
+        entry: Box<RepoEntry>,
+        reloaded: Vec<GitEvent>,
+    },
+    Log {
+        repo: PathBuf,
+        skip: usize,
+        result: std::result::Result<LogPage, RepoError>,
+    },
+    CommitDetail {
+        repo: PathBuf,
+        id: git2::Oid,
+        result: std::result::Result<CommitDetail, RepoError>,
+    },
+    Branches {
+        repo: PathBuf,
+        result: std::result::Result<BranchList, RepoError>,
+    },
+    Branch {
+        repo: PathBuf,
+        op: BranchOp,
+        /// `Some` carries a note for the status line, like a stash made first
+        result: std::result::Result<Option<String>, RepoError>,
+        entry: Box<RepoEntry>,
+        reloaded: Vec<GitEvent>,
+    },
+    Stashes {
+        repo: PathBuf,
+        result: std::result::Result<Vec<StashEntry>, RepoError>,
+    },
+    StashPreview {
+        repo: PathBuf,
+        id: git2::Oid,
+        result: std::result::Result<StashPreview, RepoError>,
+    },
+    Stash {
+        repo: PathBuf,
+        op: StashOp,
+        /// Number of paths the apply left conflicted
+        result: std::result::Result<usize, RepoError>,
+        entry: Box<RepoEntry>,
+        reloaded: Vec<GitEvent>,
+    },
+    /// A fetch, pull or push is transferring objects
+    RemoteProgress {
+        repo: PathBuf,
+        progress: TransferProgress,
+    },
+    Remote {
+        repo: PathBuf,
+        op: RemoteOp,
+        /// One-line summary of what changed
+        result: std::result::Result<String, RepoError>,
+        entry: Box<RepoEntry>,
+        reloaded: Vec<GitEvent>,
+    },
+    Worktrees {
+        repo: PathBuf,
+        result: std::result::Result<Vec<WorktreeInfo>, RepoError>,
+    },
+    Worktree {
+        repo: PathBuf,
+        op: WorktreeOp,
+        result: std::result::Result<(), RepoError>,
+        entry: Box<RepoEntry>,
+        reloaded: Vec<GitEvent>,
+    },
+    Submodules {
+        repo: PathBuf,
+        result: std::result::Result<Vec<SubmoduleInfo>, RepoError>,
+    },
+    Submodule {
+        repo: PathBuf,
+        op: SubmoduleOp,
+        result: std::result::Result<(), RepoError>,
+        entry: Box<RepoEntry>,
+        reloaded: Vec<GitEvent>,
+    },
+    StaleBranches(StaleReport),
+    DeletedBranches {
+        deleted: Vec<Deleted>,
+        /// Log the restore commands were appended to
+        recorded: std::result::Result<PathBuf, String>,
+        reloaded: Vec<GitEvent>,
+    },
+}
+
+impl GitRequest {
+    /// Blocking; call from `spawn_blocking`. `config` decides how refreshed
+    /// repo rows are counted.
+    pub fn run(self, config: &GitConfig) -> GitEvent {
+        match self {
+            GitRequest::Files(repo) => {
+                let result =
+                    files::collect_files(&repo, config.status(&repo)).map_err(RepoError::from);
+                GitEvent::Files { repo, result }
+            }
+            GitRequest::Diff { repo, path, mode } => {
+                let result = diff::diff_file(&repo, &path, mode).map_err(RepoError::from);
+                GitEvent::Diff {
+                    repo,
+                    path,
+                    mode,
+                    result,
+                }
+            }
+            GitRequest::Index { repo, op, reload } => {
+                let result = index::apply(&repo, &op).map_err(RepoError::from);
+                GitEvent::Index {
+                    entry: Box::new(collect_repo_entry(&repo, config)),
+                    reloaded: reload.into_iter().map(|r| r.run(config)).collect(),
+                    repo,
+                    op,
+                    result,
+                }
+            }
+            GitRequest::PrepareCommit(repo) => {
+                let result = commit::prepare(&repo).map_err(RepoError::from);
+                GitEvent::CommitDraft { repo, result }
+            }
+            GitRequest::Commit {
+                repo,
+                message,
+                amend,
+                reload,
+            } => {
+                let result = commit::commit(&repo, &message, amend)
+                    .map(|oid| oid.to_string()[..7].to_string())
+                    .map_err(RepoError::from);
+                GitEvent::Committed {
+                    entry: Box::new(collect_repo_entry(&repo, config)),
+                    reloaded: reload.into_iter().map(|r| r.run(config)).collect(),
+                    repo,
+                    result,
+                }
+            }
+            GitRequest::Log {
+                repo,
+                skip,
+                limit,
+                find,
+            } => {
+                let result =
+                    log::read_log(&repo, skip, limit, find.as_deref()).map_err(RepoError::from);
+                GitEvent::Log { repo, skip, result }
+            }
+            GitRequest::CommitDetail { repo, id } => {
+                let result = log::commit_detail(&repo, id).map_err(RepoError::from);
+                GitEvent::CommitDetail { repo, id, result }
+            }
+            GitRequest::Branches(repo) => {
+                let result = branches::list_branches(&repo).map_err(RepoError::from);
+                GitEvent::Branches { repo, result }
+            }
+            GitRequest::Branch { repo, op, reload } => {
+                let result = branches::apply(&repo, &op).map_err(RepoError::from);
+                GitEvent::Branch {
+                    entry: Box::new(collect_repo_entry(&repo, config)),
+                    reloaded: reload.into_iter().map(|r| r.run(config)).collect(),
+                    repo,
+                    op,
+                    result,
+                }
+            }
+            GitRequest::Stashes(repo) => {
+                let result = stash::list_stashes(&repo).map_err(RepoError::from);
+                GitEvent::Stashes { repo, result }
+            }
+            GitRequest::StashPreview { repo, id } => {
+                let result = stash::stash_preview(&repo, id).map_err(RepoError::from);
+                GitEvent::StashPreview { repo, id, result }
+            }
+            GitRequest::Stash { repo, op, reload } => {
+                let result = stash::apply(&repo, op).map_err(RepoError::from);
+                GitEvent::Stash {
+                    entry: Box::new(collect_repo_entry(&repo, config)),
+                    reloaded: reload.into_iter().map(|r| r.run(config)).collect(),
+                    repo,
+                    op,
+                    result,
+                }
+            }
+            GitRequest::Worktrees(repo) => {
+                let result = worktrees::read_worktrees(&repo, config.status(&repo))
+                    .map_err(RepoError::from);
+                GitEvent::Worktrees { repo, result }
+            }
+            GitRequest::Worktree { repo, op, reload } => {
+                let result = worktrees::apply(&repo, &op).map_err(RepoError::from);
+                GitEvent::Worktree {
+                    entry: Box::new(collect_repo_entry(&repo, config)),
+                    reloaded: reload.into_iter().map(|r| r.run(config)).collect(),
+                    repo,
+                    op,
+                    result,
+                }
+            }
+            GitRequest::Submodules(repo) => {
+                let result = submodules::read_submodules(&repo, config.status(&repo))
+                    .map_err(RepoError::from);
+                GitEvent::Submodules { repo, result }
+            }
+            GitRequest::Submodule { repo, op, reload } => {
+                let result = submodules::apply(&repo, &op).map_err(RepoError::from);
+                GitEvent::Submodule {
+                    entry: Box::new(collect_repo_entry(&repo, config)),
+                    reloaded: reload.into_iter().map(|r| r.run(config)).collect(),
+                    repo,
+                    op,
+                    result,
+                }
+            }
+            GitRequest::StaleBranches(repos) => {
+                GitEvent::StaleBranches(stale::find_stale(&repos, config))
+            }
+            GitRequest::DeleteBranches { branches, reload } => {
+                let deleted = stale::delete_branches(branches);
+                GitEvent::DeletedBranches {
+                    recorded: stale::record(&deleted).map_err(|e| e.to_string()),
+                    deleted,
+                    reloaded: reload.into_iter().map(|r| r.run(config)).collect(),
+                }
+            }
+        }
+    }
+}
+
+/// Blocking; the streaming counterpart of `GitRequest::run` for fetch, pull
+/// and push. Progress events go to `send` while the transfer runs, and the
+/// returned `GitEvent::Remote` re-runs `reload` like `Index`.
+pub fn run_remote(
+    config: &GitConfig,
+    repo: PathBuf,
+    op: RemoteOp,
+    reload: Vec<GitRequest>,
+    cancel: &AtomicBool,
+    mut send: impl FnMut(GitEvent),
+) -> GitEvent {
+    let result = remote::run(&repo, op, cancel, |progress| {
+        send(GitEvent::RemoteProgress {
+            repo: repo.clone(),
+            progress,
+        })
+    })
+    .map_err(RepoError::from);
+    GitEvent::Remote {
+        entry: Box::new(collect_repo_entry(&repo, config)),
+        reloaded: reload.into_iter().map(|r| r.run(config)).collect(),
+        repo,
+        op,
+        result,
+    }
+}
+
+impl From<git2::Error> for RepoError {
+    fn from(e: git2::Error) -> Self {
+        Self {
+            message: e.message().to_string(),
+            class: e.class(),
+            code: e.code(),
+            raw_code: e.raw_code(),
+        }
+    }
+}
+
+impl RepoEntry {
+    /// Status of a repo that was read successfully.
+    pub fn ok_status(&self) -> Option<&RepoStatus> {
+        match &self.status {
+            Some(Ok(status)) => Some(status),
+            _ => None,
+        }
+    }
+}
+
+impl HeadState {
+    pub fn label(&self) -> String {
+        match self {
+            HeadState::Branch(name) => name.clone(),
+            HeadState::Detached(sha) => format!("detached at {sha}"),
+            HeadState::Unborn(name) => format!("{name} (no commits)"),
+        }
+    }
+}
+
+impl RepoState {
+    /// Badge for the table, `None` when nothing is in progress.
+    pub fn label(&self) -> Option<String> {
+        let label = match self {
+            RepoState::Clean => return None,
+            RepoState::Merge => "MERGING".to_string(),
+            RepoState::Rebase { interactive, step } => {
+                let mut label = if *interactive {
+                    "REBASE-i".to_string()
+                } else {
+                    "REBASING".to_string()
+                };
+                if let Some((current, total)) = step {
+                    label.push_str(&format!(" {current}/{total}"));
+                }
+                label
+            }
+            RepoState::CherryPick => "CHERRY-PICKING".to_string(),
+            RepoState::Revert => "REVERTING".to_string(),
+            RepoState::Bisect => "BISECTING".to_string(),
+            RepoState::ApplyMailbox => "AM".to_string(),
+        };
+        Some(label)
+    }
+}
+
+impl Upstream {
+    pub fn label(&self) -> String {
+        match self {
+            Upstream::None => "no upstream".to_string(),
+            Upstream::Tracking { name, .. } => name.clone(),
+            Upstream::Gone { name } => format!("{name} (gone)"),
+        }
+    }
+}
+
+pub struct GitSource {
+    repo_paths: Vec<PathBuf>,
+    /// Latest result of scanning `scan_roots`, refreshed in the background.
+    discovered: Vec<PathBuf>,
+    discovered_rx: Option<mpsc::UnboundedReceiver<Vec<PathBuf>>>,
+    scan_error: Option<String>,
+    auto_fetch: AutoFetch,
+    watcher: RepoWatcher,
+    reader: StatusReader,
+    config: Arc<GitConfig>,
+    /// Latest entry per repo; watched repos are only re-read when they change
+    /// or every `watch_fallback`
+    entries: HashMap<PathBuf, RepoEntry>,
+    /// When each repo was last read, for per-repo intervals
+    last_read: HashMap<PathBuf, Instant>,
+    /// Repo order of the last snapshot
+    paths: Vec<PathBuf>,
+    watch_fallback: Duration,
+    /// Shortest poll interval of any repo
+    interval: Duration,
+}
+
+impl GitSource {
+    pub fn new(config: &GitConfig) -> Self {
+        let interval = config
+            .repos
+            .iter()
+            .filter_map(|r| r.interval_secs)
+            .fold(config.interval_secs, u64::min);
+        let mut scan_error = None;
+        let discovered_rx = (!config.scan_roots.is_empty()).then(|| {
+            let ignore = build_globset(&config.scan_ignore).unwrap_or_else(|e| {
+                scan_error = Some(format!("invalid scan_ignore: {e}"));
+                globset::GlobSet::empty()
+            });
+            spawn_discovery(
+                DiscoveryOptions {
+                    roots: config.scan_roots.iter().map(PathBuf::from).collect(),
+                    max_depth: config.scan_max_depth,
+                    ignore,
+                    follow_symlinks: config.scan_follow_symlinks,
+                },
+                Duration::from_secs(config.scan_interval_secs),
+            )
+        });
+
+        Self {
+            repo_paths: config
+                .repos
+                .iter()
+                .map(|r| PathBuf::from(&r.path))
+                .collect(),
+            discovered: Vec::new(),
+            discovered_rx,
+            scan_error,
+            auto_fetch: AutoFetch::new(config),
+            watcher: RepoWatcher::new(config.watch),
+            reader: StatusReader::new(
+                Arc::new(config.clone()),
+                config.status_workers,
+                Duration::from_secs(config.status_timeout_secs),
+            ),
+            config: Arc::new(config.clone()),
+            entries: HashMap::new(),
+            last_read: HashMap::new(),
+            paths: Vec::new(),
+            watch_fallback: Duration::from_secs(config.watch_fallback_secs),
+            interval: Duration::from_secs(interval.max(1)),
+        }
+    }
+
+    /// Explicitly configured repos first, then discovered ones not already
+    /// listed (compared by canonical path).
+    fn all_repo_paths(&mut self) -> Vec<PathBuf> {
+        if let Some(rx) = &mut self.discovered_rx {
+            while let Ok(paths) = rx.try_recv() {
+                self.discovered = paths;
+            }
+        }
+
+        let mut seen: HashSet<PathBuf> = HashSet::new();
+        let mut paths = Vec::with_capacity(self.repo_paths.len() + self.discovered.len());
+        for path in self.repo_paths.iter().chain(&self.discovered) {
+            let key = std::fs::canonicalize(path).unwrap_or_else(|_| path.clone());
+            if seen.insert(key) {
+                paths.push(path.clone());
+            }
+        }
+        paths
+    }
+
+    /// Start re-reading `stale` repos and wait a moment for them, so fast
+    /// repos land in one snapshot; slower ones stream in from `run`.
+    async fn reload(&mut self, stale: Vec<PathBuf>) {
+        let now = Instant::now();
+        for path in &stale {
+            self.last_read.insert(path.clone(), now);
+        }
+        self.reader.start(stale);
+        let grace = tokio::time::sleep(READ_GRACE);
+        tokio::pin!(grace);
+        while self.reader.is_busy() {
+            tokio::select! {
+                _ = &mut grace => break,
+                read = self.reader.next() => self.on_read(read),
+            }
+        }
+    }
+
+    /// Store a finished read. A repo that timed out keeps its last status
+    /// (a first read shows the timeout) while the read goes on.
+    fn on_read(&mut self, read: Read) {
+        let entry = match read {
+            Read::Done(entry) => *entry,
+            Read::TimedOut(path) => {
+                if self.entries.contains_key(&path) {
+                    return;
+                }
+                RepoEntry {
+                    name: repo_name(&self.config, &path),
+                    status: Some(Err(RepoError::from(git2::Error::from_str(&format!(
+                        "no response after {}s, still reading",
+                        self.reader.timeout().as_secs()
+                    ))))),
+                    scanning: true,
+                    fetch_error: None,
+                    path,
+                }
+            }
+        };
+        if self.paths.contains(&entry.path) {
+            self.entries.insert(entry.path.clone(), entry);
+        }
+    }
+
+    /// Table row for `path`, with a placeholder until its first read lands.
+    fn row(&self, path: &Path) -> RepoEntry {
+        let mut entry = self
+            .entries
+            .get(path)
+            .cloned()
+            .unwrap_or_else(|| RepoEntry {
+                path: path.to_path_buf(),
+                name: repo_name(&self.config, path),
+                status: None,
+                scanning: false,
+                fetch_error: None,
+            });
+        entry.scanning = self.reader.is_reading(path);
+        entry.fetch_error = self.auto_fetch.error(path).map(str::to_string);
+        entry
+    }
+
+    fn snapshot(&self) -> GitSnapshot {
+        GitSnapshot {
+            repos: self.paths.iter().map(|p| self.row(p)).collect(),
+            scan_error: self.scan_error.clone(),
+        }
+    }
+
+    /// Re-read the repos whose files changed. `None` when every change was
+    /// to something the table doesn't show, like an ignored build output.
+    async fn on_change(&mut self, changed: HashMap<PathBuf, Vec<PathBuf>>) -> Option<DataSnapshot> {
+        let stale = tokio::task::spawn_blocking(move || {
+            changed
+                .into_iter()
+                .filter(|(repo, paths)| watch::is_relevant(repo, paths))
+                .map(|(repo, _)| repo)
+                .collect::<Vec<_>>()
+        })
+        .await
+        .unwrap_or_default();
+        if stale.is_empty() {
+            return None;
+        }
+        self.reload(stale).await;
+        Some(DataSnapshot::Git(self.snapshot()))
+    }
+}
+
+/// Rescan the discovery roots every `interval` so new clones show up
+/// without restarting.
+fn spawn_discovery(
+    options: DiscoveryOptions,
+    interval: Duration,
+) -> mpsc::UnboundedReceiver<Vec<PathBuf>> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let options = options.clone();
+            let Ok(found) =
+                tokio::task::spawn_blocking(move || discovery::discover_repos(&options)).await
+            else {
+                continue;
+            };
+            if tx.send(found).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+/// The configured display name, else the directory name.
+fn repo_name(config: &GitConfig, path: &Path) -> String {
+    if let Some(name) = config.display_name(path) {
+        return name.to_string();
+    }
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string())
+}
+
+fn collect_repo_entry(path: &Path, config: &GitConfig) -> RepoEntry {
+    RepoEntry {
+        path: path.to_path_buf(),
+        name: repo_name(config, path),
+        status: Some(collect_repo_status(path, config.status(path)).map_err(RepoError::from)),
+        scanning: false,
+        fetch_error: None,
+    }
+}
+
+/// git2 options for `config`; callers add anything view-specific.
+fn status_options(config: &StatusConfig) -> git2::StatusOptions {
+    let mut opts = git2::StatusOptions::new();
+    opts.include_untracked(config.include_untracked)
+        .recurse_untracked_dirs(config.include_untracked && config.recurse_untracked_dirs)
+        .include_ignored(config.include_ignored)
+        .exclude_submodules(config.ignore_submodules);
+    for pathspec in &config.pathspecs {
+        opts.pathspec(pathspec);
+    }
+    opts
+}
+
+fn collect_repo_status(
+    path: &Path,
+    config: &StatusConfig,
+) -> std::result::Result<RepoStatus, git2::Error> {
+    let repo = Repository::open(path)?;
+
+    let head = resolve_head(&repo)?;
+    let upstream = match &head {
+        HeadState::Branch(name) => resolve_upstream(&repo, name)?,
+        HeadState::Detached(_) | HeadState::Unborn(_) => Upstream::None,
+    };
+
+    let counts = count_changes(&repo, config)?;
+
+    Ok(RepoStatus {
+        head,
+        state: resolve_state(&repo),
+        conflicts: counts.conflicts,
+        upstream,
+        modified: counts.modified,
+        staged: counts.staged,
+        untracked: counts.untracked,
+        stashes: stash::count_stashes(&repo),
+        ignored: config.include_ignored.then_some(counts.ignored),
+        last_fetched: last_fetched(&repo),
+        last_commit: last_commit(&repo),
+        last_change: counts
+            .last_change
+            .max(mtime(&repo.path().join("index")))
+            .max(mtime(&repo.path().join("logs/HEAD"))),
+        // Linked worktrees are listed under their main repo. Failing to read
+        // them leaves the repo's own row intact.
+        worktrees: if repo.is_worktree() {
+            Vec::new()
+        } else {
+            worktrees::list_worktrees(&repo, config).unwrap_or_default()
+        },
+        // Same for submodules
+        submodules: submodules::list_submodules(&repo, config).unwrap_or_default(),
+    })
+}
+
+#[derive(Debug, Default)]
+struct ChangeCounts {
+    conflicts: usize,
+    ignored: usize,
+    modified: usize,
+    staged: usize,
+    untracked: usize,
+    /// Latest mtime among changed worktree files
+    last_change: Option<i64>,
+}
+
+fn count_changes(
+    repo: &Repository,
+    config: &StatusConfig,
+) -> std::result::Result<ChangeCounts, git2::Error> {
+    let statuses = repo.statuses(Some(&mut status_options(config)))?;
+    let mut counts = ChangeCounts::default();
+    // Submodules get rows of their own, so a moved or dirty checkout isn't
+    // counted as a modified file here
+    let submodules: HashSet<PathBuf> = repo
+        .submodules()
+        .map(|subs| subs.iter().map(|s| s.path().to_path_buf()).collect())
+        .unwrap_or_default();
+
+    for entry in statuses.iter() {
+        let s = entry.status();
+        if s.contains(git2::Status::CONFLICTED) {
+            counts.conflicts += 1;
+            continue;
+        }
+        if s.contains(git2::Status::IGNORED) {
+            counts.ignored += 1;
+            continue;
+        }
+        let submodule = entry
+            .path()
+            .is_some_and(|p| submodules.contains(Path::new(p)));
+        if !submodule
+            && s.intersects(
+                git2::Status::WT_MODIFIED
+                    | git2::Status::WT_DELETED
+                    | git2::Status::WT_RENAMED
+                    | git2::Status::WT_TYPECHANGE,
+            )
+        {
+            counts.modified += 1;
+        }
+        if s.intersects(
+            git2::Status::INDEX_NEW
+                | git2::Status::INDEX_MODIFIED
+                | git2::Status::INDEX_DELETED
+                | git2::Status::INDEX_RENAMED
+                | git2::Status::INDEX_TYPECHANGE,
+        ) {
+            counts.staged += 1;
+        }
+        if s.contains(git2::Status::WT_NEW) {
+            counts.untracked += 1;
+        }
+        if let (Some(workdir), Some(path)) = (repo.workdir(), entry.path()) {
+            if s.intersects(
+                git2::Status::WT_NEW
+                    | git2::Status::WT_MODIFIED
+                    | git2::Status::WT_RENAMED
+                    | git2::Status::WT_TYPECHANGE,
+            ) {
+                counts.last_change = counts.last_change.max(mtime(&workdir.join(path)));
+            }
+        }
+    }
+    Ok(counts)
+}
+
+/// Seconds since the epoch of the last write to `path`.
+fn mtime(path: &Path) -> Option<i64> {
+    std::fs::symlink_metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .map(|time| chrono::DateTime::<chrono::Utc>::from(time).timestamp())
+}
+
+/// Every fetch rewrites `FETCH_HEAD`, so its mtime dates the last one.
+fn last_fetched(repo: &Repository) -> Option<i64> {
+    mtime(&repo.path().join("FETCH_HEAD"))
+}
+
+fn last_commit(repo: &Repository) -> Option<LastCommit> {
+    let commit = repo.head().ok()?.peel_to_commit().ok()?;
+    let author = commit.author().name().unwrap_or_default().to_string();
+    Some(LastCommit {
+        id: commit.id().to_string()[..7].to_string(),
+        author,
+        time: commit.time().seconds(),
+        subject: commit.summary().unwrap_or_default().to_string(),
+    })
+}
+
+fn resolve_head(repo: &Repository) -> std::result::Result<HeadState, git2::Error> {
+    match repo.head() {
+        Ok(head) if repo.head_detached()? => {
+            let sha = head
+                .target()
+                .map(|oid| oid.to_string()[..7].to_string())
+                .unwrap_or_default();
+            Ok(HeadState::Detached(sha))
+        }
+        Ok(head) => Ok(HeadState::Branch(
+            head.shorthand().unwrap_or("HEAD").to_string(),
+        )),
+        Err(e) if e.code() == git2::ErrorCode::UnbornBranch => {
+            let head = repo.find_reference("HEAD")?;
+            let target = head.symbolic_target().unwrap_or("HEAD");
+            let name = target.strip_prefix("refs/heads/").unwrap_or(target);
+            Ok(HeadState::Unborn(name.to_string()))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn resolve_state(repo: &Repository) -> RepoState {
+    use git2::RepositoryState as S;
+    match repo.state() {
+        S::Clean => RepoState::Clean,
+        S::Merge => RepoState::Merge,
+        S::Revert | S::RevertSequence => RepoState::Revert,
+        S::CherryPick | S::CherryPickSequence => RepoState::CherryPick,
+        S::Bisect => RepoState::Bisect,
+        S::Rebase | S::RebaseMerge | S::ApplyMailboxOrRebase => RepoState::Rebase {
+            interactive: false,
+            step: rebase_step(repo),
+        },
+        S::RebaseInteractive => RepoState::Rebase {
+            interactive: true,
+            step: rebase_step(repo),
+        },
+        S::ApplyMailbox => RepoState::ApplyMailbox,
+    }
+}
+
+/// Progress of a rebase from the files git keeps under `.git`:
+/// `rebase-merge/{msgnum,end}` for merge-based and interactive rebases,
+/// `rebase-apply/{next,last}` for the older apply backend.
+fn rebase_step(repo: &Repository) -> Option<(usize, usize)> {
+    let read = |dir: &str, file: &str| -> Option<usize> {
+        std::fs::read_to_string(repo.path().join(dir).join(file))
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
+    };
+    read("rebase-merge", "msgnum")
+        .zip(read("rebase-merge", "end"))
+        .or_else(|| read("rebase-apply", "next").zip(read("rebase-apply", "last")))
+}
+
+/// Resolve the upstream configured for `branch` (`branch.<name>.remote` and
+/// `branch.<name>.merge`) rather than assuming `origin/<branch>`.
+fn resolve_upstream(repo: &Repository, branch: &str) -> std::result::Result<Upstream, git2::Error> {
+    let local = repo.find_branch(branch, git2::BranchType::Local)?;
+    let refname = format!("refs/heads/{branch}");
+
+    let upstream_ref = match repo.branch_upstream_name(&refname) {
+        Ok(buf) => buf.as_str().unwrap_or_default().to_string(),
+        // No upstream configured, or its remote was removed
+        Err(_) => return Ok(configured_upstream(repo, branch)),
+    };
+    let name = upstream_ref
+        .strip_prefix("refs/remotes/")
+        .or_else(|| upstream_ref.strip_prefix("refs/heads/"))
+        .unwrap_or(&upstream_ref)
+        .to_string();
+
+    let upstream_oid = match repo.refname_to_id(&upstream_ref) {
+        Ok(oid) => oid,
+        Err(_) => return Ok(Upstream::Gone { name }),
+    };
+    let (ahead, behind) = match local.get().target() {
+        Some(local_oid) => repo.graph_ahead_behind(local_oid, upstream_oid)?,
+        None => (0, 0),
+    };
+
+    Ok(Upstream::Tracking {
+        name,
+        ahead,
+        behind,
+    })
+}
+
+/// The upstream as written in the branch's config, for when libgit2 can't
+/// resolve it: `Gone` if a merge ref is configured, else `None`.
+fn configured_upstream(repo: &Repository, branch: &str) -> Upstream {
+    let Ok(config) = repo.config() else {
+        return Upstream::None;
+    };
+    let Ok(merge) = config.get_string(&format!("branch.{branch}.merge")) else {
+        return Upstream::None;
+    };
+    let merge = merge.strip_prefix("refs/heads/").unwrap_or(&merge);
+    let name = match config.get_string(&format!("branch.{branch}.remote")) {
+        Ok(remote) if remote != "." => format!("{remote}/{merge}"),
+        _ => merge.to_string(),
+    };
+    Upstream::Gone { name }
+}
+
+/// How long a refresh waits for repo reads before sending what it has.
+const READ_GRACE: Duration = Duration::from_millis(250);
+
+/// What woke `GitSource::run`.
+enum Wake {
+    Poll,
+    Changed(HashMap<PathBuf, Vec<PathBuf>>),
+    Read(Read),
+}
+
+#[async_trait]
+impl DataSource for GitSource {
+    /// Re-reads repos due for a poll: unwatched ones every `interval_secs`
+    /// (their own or the global one), watched ones when they changed or every
+    /// `watch_fallback`.
+    async fn collect(&mut self) -> Result<DataSnapshot> {
+        self.paths = self.all_repo_paths();
+        self.watcher.sync(&self.paths).await;
+        self.entries.retain(|path, _| self.paths.contains(path));
+        self.last_read.retain(|path, _| self.paths.contains(path));
+
+        let changed = self.watcher.take_pending();
+        let stale = self
+            .paths
+            .iter()
+            .filter(|p| {
+                let period = if self.watcher.is_watched(p) {
+                    self.watch_fallback
+                } else {
+                    Duration::from_secs(self.config.interval_secs(p))
+                };
+                // A little slack so ticks of the same length count as due
+                let due = self
+                    .last_read
+                    .get(*p)
+                    .is_none_or(|at| at.elapsed() + Duration::from_millis(50) >= period);
+                due || changed.contains_key(*p)
+            })
+            .cloned()
+            .collect();
+        self.reload(stale).await;
+
+        self.auto_fetch.reap();
+        let snapshot = self.snapshot();
+        self.auto_fetch.schedule(&snapshot.repos);
+        Ok(DataSnapshot::Git(snapshot))
+    }
+
+    /// Polls like other sources, and in between refreshes single repos as
+    /// soon as their files change.
+    async fn run(mut self, tx: mpsc::UnboundedSender<DataSnapshot>)
+    where
+        Self: Sized,
+    {
+        let mut interval = tokio::time::interval(self.interval());
+        loop {
+            let wake = tokio::select! {
+                _ = interval.tick() => Wake::Poll,
+                changed = self.watcher.changed() => Wake::Changed(changed),
+                read = self.reader.next() => Wake::Read(read),
+            };
+            let snapshot = match wake {
+                Wake::Poll => self.collect().await.ok(),
+                Wake::Changed(changed) => self.on_change(changed).await,
+                Wake::Read(read) => {
+                    let path = match &read {
+                        Read::Done(entry) => entry.path.clone(),
+                        Read::TimedOut(path) => path.clone(),
+                    };
+                    self.on_read(read);
+                    self.paths
+                        .contains(&path)
+                        .then(|| DataSnapshot::GitRepo(Box::new(self.row(&path))))
+                }
+            };
+            if let Some(snapshot) = snapshot {
+                if tx.send(snapshot).is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    fn interval(&self) -> Duration {
+        self.interval
+    }
+}
+
@@ -0,0 +1,113 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{mpsc, Semaphore};
+
+use super::{collect_repo_entry, repo_name, RepoEntry, RepoError};
+use crate::config::GitConfig;
+
+/// Outcome of one repo read.
+pub enum Read {
+    Done(Box<RepoEntry>),
+    /// No result within the timeout; the read keeps going and a `Done`
+    /// follows when it finishes
+    TimedOut(PathBuf),
+}
+
+/// Reads repo status on the blocking pool, a few repos at a time, so one
+/// huge or NFS-mounted repo doesn't hold up every other row.
+pub struct StatusReader {
+    config: Arc<GitConfig>,
+    workers: Arc<Semaphore>,
+    timeout: Duration,
+    /// Repos with a read running, including timed-out ones still going
+    reading: HashSet<PathBuf>,
+    tx: mpsc::UnboundedSender<Read>,
+    rx: mpsc::UnboundedReceiver<Read>,
+}
+
+impl StatusReader {
+    pub fn new(config: Arc<GitConfig>, workers: usize, timeout: Duration) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            config,
+            workers: Arc::new(Semaphore::new(workers.max(1))),
+            timeout,
+            reading: HashSet::new(),
+            tx,
+            rx,
+        }
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn is_reading(&self, path: &Path) -> bool {
+        self.reading.contains(path)
+    }
+
+    pub fn is_busy(&self) -> bool {
+        !self.reading.is_empty()
+    }
+
+    /// Queue reads of `paths`, skipping repos already being read.
+    pub fn start(&mut self, paths: impl IntoIterator<Item = PathBuf>) {
+        for path in paths {
+            if !self.reading.insert(path.clone()) {
+                continue;
+            }
+            let config = self.config.clone();
+            let workers = self.workers.clone();
+            let timeout = self.timeout;
+            let tx = self.tx.clone();
+            tokio::spawn(async move {
+                let Ok(permit) = workers.acquire_owned().await else {
+                    return;
+                };
+                // The permit goes with the thread, so a hung read keeps
+                // its worker until it really returns
+                let read = {
+                    let path = path.clone();
+                    let config = config.clone();
+                    tokio::task::spawn_blocking(move || {
+                        let _permit = permit;
+                        collect_repo_entry(&path, &config)
+                    })
+                };
+                tokio::pin!(read);
+                let result = match tokio::time::timeout(timeout, &mut read).await {
+                    Ok(result) => result,
+                    Err(_) => {
+                        let _ = tx.send(Read::TimedOut(path.clone()));
+                        read.await
+                    }
+                };
+                let entry = result.unwrap_or_else(|e| RepoEntry {
+                    name: repo_name(&config, &path),
+                    status: Some(Err(RepoError::from(git2::Error::from_str(&format!(
+                        "status read failed: {e}"
+                    ))))),
+                    scanning: false,
+                    fetch_error: None,
+                    path,
+                });
+                let _ = tx.send(Read::Done(Box::new(entry)));
+            });
+        }
+    }
+
+    /// The next read to finish or time out. Safe to cancel.
+    pub async fn next(&mut self) -> Read {
+        // `self.tx` keeps the channel open, so `None` can't happen
+        let Some(read) = self.rx.recv().await else {
+            return std::future::pending().await;
+        };
+        if let Read::Done(entry) = &read {
+            self.reading.remove(&entry.path);
+        }
+        read
+    }
+}
@@ -0,0 +1,614 @@
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use git2::build::CheckoutBuilder;
+use git2::{
+    Cred, CredentialType, ErrorClass, ErrorCode, FetchOptions, PushOptions, Remote,
+    RemoteCallbacks, Repository,
+};
+
+/// Progress is reported at most this often, plus once when a phase ends.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteOp {
+    Fetch,
+    /// Fetch, then fast-forward the current branch to its upstream
+    Pull,
+    /// Push the current branch to a branch of the same name on its
+    /// upstream's remote, else the default one
+    Push,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Receiving,
+    Resolving,
+    Sending,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferProgress {
+    pub phase: Phase,
+    pub current: usize,
+    pub total: usize,
+    pub bytes: usize,
+}
+
+impl RemoteOp {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            RemoteOp::Fetch => "fetch",
+            RemoteOp::Pull => "pull",
+            RemoteOp::Push => "push",
+        }
+    }
+
+    /// Present participle for progress lines, e.g. `Fetching`.
+    pub fn verb(&self) -> &'static str {
+        match self {
+            RemoteOp::Fetch => "Fetching",
+            RemoteOp::Pull => "Pulling",
+            RemoteOp::Push => "Pushing",
+        }
+    }
+}
+
+impl Phase {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Phase::Receiving => "Receiving objects",
+            Phase::Resolving => "Resolving deltas",
+            Phase::Sending => "Writing objects",
+        }
+    }
+}
+
+fn error(code: ErrorCode, class: ErrorClass, message: impl AsRef<str>) -> git2::Error {
+    git2::Error::new(code, class, message.as_ref())
+}
+
+/// Reword the errors people actually hit into something actionable.
+pub(super) fn explain(e: git2::Error) -> git2::Error {
+    let message = match (e.code(), e.class()) {
+        (ErrorCode::Auth, _) => format!("authentication failed: {}", e.message()),
+        (ErrorCode::Certificate, _) => format!("host verification failed: {}", e.message()),
+        (ErrorCode::Conflict, _) => format!("local changes would be overwritten: {}", e.message()),
+        _ => return e,
+    };
+    git2::Error::new(e.code(), e.class(), message)
+}
+
+/// Credentials from ssh-agent for ssh URLs and from git's credential
+/// helpers for https ones. Each kind is offered once; libgit2 keeps asking
+/// after a rejection, which would otherwise loop forever.
+pub(super) fn credentials<'a>(
+    repo: &'a Repository,
+) -> impl FnMut(&str, Option<&str>, CredentialType) -> Result<Cred, git2::Error> + 'a {
+    let mut tried = CredentialType::empty();
+    move |url, username, allowed| {
+        if allowed.contains(CredentialType::USERNAME) && !tried.contains(CredentialType::USERNAME) {
+            tried |= CredentialType::USERNAME;
+            return Cred::username(username.unwrap_or("git"));
+        }
+        if allowed.contains(CredentialType::SSH_KEY) && !tried.contains(CredentialType::SSH_KEY) {
+            tried |= CredentialType::SSH_KEY;
+            return Cred::ssh_key_from_agent(username.unwrap_or("git"));
+        }
+        if allowed.contains(CredentialType::USER_PASS_PLAINTEXT)
+            && !tried.contains(CredentialType::USER_PASS_PLAINTEXT)
+        {
+            tried |= CredentialType::USER_PASS_PLAINTEXT;
+            let config = repo.config()?;
+            return Cred::credential_helper(&config, url, username);
+        }
+        if allowed.contains(CredentialType::DEFAULT) && !tried.contains(CredentialType::DEFAULT) {
+            tried |= CredentialType::DEFAULT;
+            return Cred::default();
+        }
+        Err(error(
+            ErrorCode::Auth,
+            ErrorClass::Net,
+            format!("no accepted credentials for {url} (tried ssh-agent and credential helpers)"),
+        ))
+    }
+}
+
+/// Calls `progress` at most every `PROGRESS_INTERVAL`, always passing
+/// through the last update of a phase.
+struct Throttle<F> {
+    progress: F,
+    last: Option<Instant>,
+}
+
+impl<F: FnMut(TransferProgress)> Throttle<F> {
+    fn report(&mut self, update: TransferProgress) {
+        let due = self.last.is_none_or(|t| t.elapsed() >= PROGRESS_INTERVAL);
+        if due || update.current == update.total {
+            self.last = Some(Instant::now());
+            (self.progress)(update);
+        }
+    }
+}
+
+/// Remote of the current branch's upstream, else `origin`, else the only
+/// remote.
+fn default_remote<'r>(repo: &'r Repository) -> Result<Remote<'r>, git2::Error> {
+    if let Some(branch) = current_branch(repo) {
+        if let Ok(name) = repo.branch_upstream_remote(&format!("refs/heads/{branch}")) {
+            match name.as_str() {
+                // `git branch --track` from a local branch
+                Some(".") => {
+                    return Err(error(
+                        ErrorCode::Invalid,
+                        ErrorClass::Config,
+                        format!("{branch} tracks a local branch; there is no remote to talk to"),
+                    ))
+                }
+                Some(name) => return repo.find_remote(name),
+                None => {}
+            }
+        }
+    }
+    if let Ok(origin) = repo.find_remote("origin") {
+        return Ok(origin);
+    }
+    let remotes = repo.remotes()?;
+    match (remotes.len(), remotes.get(0)) {
+        (1, Some(name)) => repo.find_remote(name),
+        (0, _) => Err(error(
+            ErrorCode::NotFound,
+            ErrorClass::Config,
+            "no remote configured",
+        )),
+        _ => Err(error(
+            ErrorCode::Ambiguous,
+            ErrorClass::Config,
+            "several remotes and no upstream or origin; set an upstream first",
+        )),
+    }
+}
+
+fn current_branch(repo: &Repository) -> Option<String> {
+    let head = repo.head().ok().filter(|h| h.is_branch())?;
+    head.shorthand().map(str::to_string)
+}
+
+fn fetch(
+    repo: &Repository,
+    cancel: &AtomicBool,
+    progress: impl FnMut(TransferProgress),
+) -> Result<String, git2::Error> {
+    let mut remote = default_remote(repo)?;
+    let mut throttle = Throttle {
+        progress,
+        last: None,
+    };
+    let mut updated = 0;
+
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(credentials(repo));
+    callbacks.transfer_progress(|stats| {
+        let update = if stats.received_objects() < stats.total_objects() {
+            TransferProgress {
+                phase: Phase::Receiving,
+                current: stats.received_objects(),
+                total: stats.total_objects(),
+                bytes: stats.received_bytes(),
+            }
+        } else {
+            TransferProgress {
+                phase: Phase::Resolving,
+                current: stats.indexed_deltas(),
+                total: stats.total_deltas(),
+                bytes: stats.received_bytes(),
+            }
+        };
+        throttle.report(update);
+        !cancel.load(Ordering::Relaxed)
+    });
+    callbacks.update_tips(|_, _, _| {
+        updated += 1;
+        true
+    });
+
+    let mut opts = FetchOptions::new();
+    opts.remote_callbacks(callbacks);
+    remote.fetch::<&str>(&[], Some(&mut opts), None)?;
+    drop(opts);
+
+    let name = remote.name().unwrap_or("remote").to_string();
+    Ok(match updated {
+        0 => format!("{name} is up to date"),
+        1 => format!("Fetched {name}: 1 ref updated"),
+        n => format!("Fetched {name}: {n} refs updated"),
+    })
+}
+
+/// Fetch, then move the current branch to its upstream if that's a
+/// fast-forward. Anything else is left for the user to merge or rebase.
+fn pull(
+    repo: &Repository,
+    cancel: &AtomicBool,
+    progress: impl FnMut(TransferProgress),
+) -> Result<String, git2::Error> {
+    let branch = current_branch(repo).ok_or_else(|| {
+        error(
+            ErrorCode::Invalid,
+            ErrorClass::Reference,
+            "HEAD is not on a branch",
+        )
+    })?;
+    let refname = format!("refs/heads/{branch}");
+    let upstream = repo.branch_upstream_name(&refname).map_err(|_| {
+        error(
+            ErrorCode::NotFound,
+            ErrorClass::Reference,
+            format!("{branch} has no upstream"),
+        )
+    })?;
+    let upstream = upstream.as_str().unwrap_or_default().to_string();
+
+    fetch(repo, cancel, progress)?;
+
+    let local = repo.refname_to_id(&refname)?;
+    let target = repo.refname_to_id(&upstream).map_err(|_| {
+        error(
+            ErrorCode::NotFound,
+            ErrorClass::Reference,
+            format!("upstream {upstream} no longer exists"),
+        )
+    })?;
+    if local == target || repo.graph_descendant_of(local, target)? {
+        return Ok(format!("{branch} is up to date"));
+    }
+    if !repo.graph_descendant_of(target, local)? {
+        return Err(error(
+            ErrorCode::NotFastForward,
+            ErrorClass::Merge,
+            format!("{branch} and its upstream have diverged; merge or rebase instead"),
+        ));
+    }
+
+    let commit = repo.find_commit(target)?;
+    repo.checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().safe()))?;
+    repo.find_reference(&refname)?
+        .set_target(target, "pull: fast-forward")?;
+    Ok(format!(
+        "Fast-forwarded {branch} to {}",
+        &target.to_string()[..7]
+    ))
+}
+
+fn push(
+    repo: &Repository,
+    cancel: &AtomicBool,
+    progress: impl FnMut(TransferProgress),
+) -> Result<String, git2::Error> {
+    let branch = current_branch(repo).ok_or_else(|| {
+        error(
+            ErrorCode::Invalid,
+            ErrorClass::Reference,
+            "HEAD is not on a branch",
+        )
+    })?;
+    let refname = format!("refs/heads/{branch}");
+    let mut remote = default_remote(repo)?;
+    let remote_name = remote.name().unwrap_or("remote").to_string();
+    // Like `push.default=simple`: only ever push to a branch of the same
+    // name, so a branch started from origin/main can't land on main
+    let merge = repo
+        .config()?
+        .get_string(&format!("branch.{branch}.merge"))
+        .ok();
+    if let Some(merge) = merge.as_deref().filter(|m| *m != refname) {
+        let upstream = merge.strip_prefix("refs/heads/").unwrap_or(merge);
+        return Err(error(
+            ErrorCode::Invalid,
+            ErrorClass::Reference,
+            format!(
+                "{branch} tracks {remote_name}/{upstream}, which has a different name; push it with git to choose where"
+            ),
+        ));
+    }
+    let set_upstream = merge.is_none();
+
+    let mut throttle = Throttle {
+        progress,
+        last: None,
+    };
+    let mut rejected = None;
+
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(credentials(repo));
+    callbacks.push_transfer_progress(|current, total, bytes| {
+        throttle.report(TransferProgress {
+            phase: Phase::Sending,
+            current,
+            total,
+            bytes,
+        });
+    });
+    // Pushes can only be called off before the pack is sent
+    callbacks.push_negotiation(|_| {
+        if cancel.load(Ordering::Relaxed) {
+            Err(error(ErrorCode::User, ErrorClass::Callback, "cancelled"))
+        } else {
+            Ok(())
+        }
+    });
+    callbacks.push_update_reference(|refname, status| {
+        if let Some(status) = status {
+            rejected = Some(format!("{refname}: {status}"));
+        }
+        Ok(())
+    });
+
+    let mut opts = PushOptions::new();
+    opts.remote_callbacks(callbacks);
+    remote
+        .push(&[format!("{refname}:{refname}")], Some(&mut opts))
+        .map_err(|e| match e.code() {
+            ErrorCode::NotFastForward => error(
+                e.code(),
+                e.class(),
+                format!("rejected by {remote_name}: non-fast-forward; pull first"),
+            ),
+            _ => e,
+        })?;
+    drop(opts);
+
+    if let Some(reason) = rejected {
+        return Err(error(
+            ErrorCode::NotFastForward,
+            ErrorClass::Reference,
+            format!("rejected by {remote_name}: {reason}"),
+        ));
+    }
+    if set_upstream {
+        repo.find_branch(&branch, git2::BranchType::Local)?
+            .set_upstream(Some(&format!("{remote_name}/{branch}")))?;
+    }
+    Ok(format!("Pushed {branch} to {remote_name}"))
+}
+
+/// Blocking; run through `Action::GitRemote`. Transfer progress goes to
+/// `progress`, and setting `cancel` aborts the transfer. Returns a one-line
+/// summary.
+pub fn run(
+    path: &Path,
+    op: RemoteOp,
+    cancel: &AtomicBool,
+    progress: impl FnMut(TransferProgress),
+) -> Result<String, git2::Error> {
+    let repo = Repository::open(path)?;
+    let result = match op {
+        RemoteOp::Fetch => fetch(&repo, cancel, progress),
+        RemoteOp::Pull => pull(&repo, cancel, progress),
+        RemoteOp::Push => push(&repo, cancel, progress),
+    };
+    // An aborting callback surfaces as a generic error, so go by the flag
+    result.map_err(|e| {
+        if cancel.load(Ordering::Relaxed) {
+            error(
+                ErrorCode::User,
+                ErrorClass::Callback,
+                format!("{} cancelled", op.describe()),
+            )
+        } else {
+            explain(e)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{Oid, RepositoryInitOptions, Signature};
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// A directory under the system temp dir, removed on drop.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path =
+                std::env::temp_dir().join(format!("ktop-remote-{name}-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// A bare `origin` with one commit on `main`, pushed from `a` and cloned
+    /// into `b`.
+    struct Setup {
+        dir: TempDir,
+        origin: PathBuf,
+    }
+
+    impl Setup {
+        fn new(name: &str) -> Self {
+            let dir = TempDir::new(name);
+            let origin = dir.0.join("origin.git");
+            let mut opts = RepositoryInitOptions::new();
+            opts.bare(true).initial_head("main");
+            Repository::init_opts(&origin, &opts).unwrap();
+
+            let mut opts = RepositoryInitOptions::new();
+            opts.initial_head("main");
+            let first = Repository::init_opts(dir.0.join("a"), &opts).unwrap();
+            first
+                .remote("origin", &format!("file://{}", origin.display()))
+                .unwrap();
+            commit(&first, "README", "hello\n");
+            run(
+                first.workdir().unwrap(),
+                RemoteOp::Push,
+                &no_cancel(),
+                |_| {},
+            )
+            .unwrap();
+
+            let setup = Self { dir, origin };
+            Repository::clone(&setup.url(), setup.dir.0.join("b")).unwrap();
+            setup
+        }
+
+        fn url(&self) -> String {
+            format!("file://{}", self.origin.display())
+        }
+
+        fn repo(&self, name: &str) -> Repository {
+            Repository::open(self.dir.0.join(name)).unwrap()
+        }
+
+        fn path(&self, name: &str) -> PathBuf {
+            self.dir.0.join(name)
+        }
+    }
+
+    fn no_cancel() -> AtomicBool {
+        AtomicBool::new(false)
+    }
+
+    fn commit(repo: &Repository, file: &str, content: &str) -> Oid {
+        fs::write(repo.workdir().unwrap().join(file), content).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(file)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &signature, &signature, file, &tree, &parents)
+            .unwrap()
+    }
+
+    fn head(repo: &Repository) -> Oid {
+        repo.head().unwrap().target().unwrap()
+    }
+
+    #[test]
+    fn fetch_updates_remote_tracking_branch() {
+        let setup = Setup::new("fetch");
+        let pushed = commit(&setup.repo("a"), "a.txt", "a\n");
+        run(&setup.path("a"), RemoteOp::Push, &no_cancel(), |_| {}).unwrap();
+
+        let summary = run(&setup.path("b"), RemoteOp::Fetch, &no_cancel(), |_| {}).unwrap();
+        assert_eq!(summary, "Fetched origin: 1 ref updated");
+        let b = setup.repo("b");
+        assert_eq!(b.refname_to_id("refs/remotes/origin/main").unwrap(), pushed);
+        assert_ne!(head(&b), pushed);
+
+        let summary = run(&setup.path("b"), RemoteOp::Fetch, &no_cancel(), |_| {}).unwrap();
+        assert_eq!(summary, "origin is up to date");
+    }
+
+    #[test]
+    fn pull_fast_forwards() {
+        let setup = Setup::new("pull");
+        let pushed = commit(&setup.repo("a"), "a.txt", "a\n");
+        run(&setup.path("a"), RemoteOp::Push, &no_cancel(), |_| {}).unwrap();
+
+        let summary = run(&setup.path("b"), RemoteOp::Pull, &no_cancel(), |_| {}).unwrap();
+        assert!(summary.starts_with("Fast-forwarded main"), "{summary}");
+        assert_eq!(head(&setup.repo("b")), pushed);
+        assert_eq!(
+            fs::read_to_string(setup.path("b").join("a.txt")).unwrap(),
+            "a\n"
+        );
+    }
+
+    #[test]
+    fn pull_refuses_diverged_branch() {
+        let setup = Setup::new("diverged");
+        commit(&setup.repo("a"), "a.txt", "a\n");
+        run(&setup.path("a"), RemoteOp::Push, &no_cancel(), |_| {}).unwrap();
+        let local = commit(&setup.repo("b"), "b.txt", "b\n");
+
+        let e = run(&setup.path("b"), RemoteOp::Pull, &no_cancel(), |_| {}).unwrap_err();
+        assert_eq!(e.code(), ErrorCode::NotFastForward);
+        assert!(e.message().contains("diverged"), "{}", e.message());
+        assert_eq!(head(&setup.repo("b")), local);
+    }
+
+    #[test]
+    fn push_rejected_when_behind() {
+        let setup = Setup::new("rejected");
+        let pushed = commit(&setup.repo("a"), "a.txt", "a\n");
+        run(&setup.path("a"), RemoteOp::Push, &no_cancel(), |_| {}).unwrap();
+        commit(&setup.repo("b"), "b.txt", "b\n");
+
+        let e = run(&setup.path("b"), RemoteOp::Push, &no_cancel(), |_| {}).unwrap_err();
+        assert_eq!(e.code(), ErrorCode::NotFastForward);
+        assert!(
+            e.message().starts_with("rejected by origin"),
+            "{}",
+            e.message()
+        );
+        let origin = Repository::open_bare(&setup.origin).unwrap();
+        assert_eq!(origin.refname_to_id("refs/heads/main").unwrap(), pushed);
+    }
+
+    #[test]
+    fn push_refuses_upstream_with_other_name() {
+        let setup = Setup::new("other-name");
+        let b = setup.repo("b");
+        let main = b.head().unwrap().peel_to_commit().unwrap();
+        b.branch("feat", &main, false)
+            .unwrap()
+            .set_upstream(Some("origin/main"))
+            .unwrap();
+        b.set_head("refs/heads/feat").unwrap();
+        commit(&b, "b.txt", "b\n");
+
+        let e = run(&setup.path("b"), RemoteOp::Push, &no_cancel(), |_| {}).unwrap_err();
+        assert!(e.message().contains("different name"), "{}", e.message());
+        let origin = Repository::open_bare(&setup.origin).unwrap();
+        assert_eq!(origin.refname_to_id("refs/heads/main").unwrap(), main.id());
+    }
+
+    #[test]
+    fn local_upstream_is_explained() {
+        let setup = Setup::new("local-upstream");
+        let b = setup.repo("b");
+        let main = b.head().unwrap().peel_to_commit().unwrap();
+        b.branch("feat", &main, false)
+            .unwrap()
+            .set_upstream(Some("main"))
+            .unwrap();
+        b.set_head("refs/heads/feat").unwrap();
+
+        let e = run(&setup.path("b"), RemoteOp::Fetch, &no_cancel(), |_| {}).unwrap_err();
+        assert!(
+            e.message().contains("tracks a local branch"),
+            "{}",
+            e.message()
+        );
+    }
+
+    #[test]
+    fn cancelled_fetch_reports_cancel() {
+        let setup = Setup::new("cancel");
+        commit(&setup.repo("a"), "a.txt", "a\n");
+        run(&setup.path("a"), RemoteOp::Push, &no_cancel(), |_| {}).unwrap();
+
+        let cancel = AtomicBool::new(true);
+        let e = run(&setup.path("b"), RemoteOp::Fetch, &cancel, |_| {}).unwrap_err();
+        assert_eq!(e.message(), "fetch cancelled");
+        let b = setup.repo("b");
+        assert_eq!(
+            b.refname_to_id("refs/remotes/origin/main").unwrap(),
+            head(&b)
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use git2::{BranchType, Oid, Repository};
+
+use super::branches::{default_branch, is_merged};
+use super::{repo_name, resolve_upstream, RepoError, Upstream};
+use crate::config::GitConfig;
+use crate::source::data_home;
+
+/// Local branches worth cleaning up, across every monitored repo.
+#[derive(Debug, Clone, Default)]
+pub struct StaleReport {
+    /// By repo, oldest tip first
+    pub branches: Vec<StaleBranch>,
+    /// Repos that couldn't be read, by display name
+    pub failed: Vec<(String, RepoError)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct StaleBranch {
+    pub repo: PathBuf,
+    pub repo_name: String,
+    pub name: String,
+    pub target: Oid,
+    pub author: String,
+    /// Seconds since the epoch, of the tip commit
+    pub time: i64,
+    pub summary: String,
+    /// Tip is reachable from the default branch
+    pub merged: bool,
+    /// Upstream is configured but its ref no longer exists
+    pub gone: bool,
+    /// No commits for `stale_days`
+    pub idle: bool,
+}
+
+impl StaleBranch {
+    /// Why the branch is listed, e.g. `merged, gone`.
+    pub fn reasons(&self) -> String {
+        let reasons: Vec<&str> = [
+            (self.merged, "merged"),
+            (self.gone, "gone"),
+            (self.idle, "idle"),
+        ]
+        .into_iter()
+        .filter_map(|(set, reason)| set.then_some(reason))
+        .collect();
+        reasons.join(", ")
+    }
+
+    pub fn key(&self) -> BranchRef {
+        BranchRef {
+            repo: self.repo.clone(),
+            name: self.name.clone(),
+            target: self.target,
+        }
+    }
+}
+
+/// A local branch as it was when the report was read.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BranchRef {
+    pub repo: PathBuf,
+    pub name: String,
+    pub target: Oid,
+}
+
+/// Outcome of deleting one branch; `branch.target` is what to recreate it
+/// from.
+#[derive(Debug, Clone)]
+pub struct Deleted {
+    pub branch: BranchRef,
+    pub result: Result<(), RepoError>,
+}
+
+impl Deleted {
+    /// Command that recreates the branch where it was.
+    pub fn restore_command(&self) -> String {
+        format!(
+            "git -C {} branch {} {}",
+            shell_quote(&self.branch.repo.to_string_lossy()),
+            shell_quote(&self.branch.name),
+            self.branch.target
+        )
+    }
+}
+
+/// Quote `s` for a POSIX shell, leaving plain words alone.
+fn shell_quote(s: &str) -> String {
+    if !s.is_empty()
+        && s.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"/-_.,:@+=%".contains(&b))
+    {
+        return s.to_string();
+    }
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+fn stale_in(path: &Path, name: &str, stale_days: u64) -> Result<Vec<StaleBranch>, git2::Error> {
+    let repo = Repository::open(path)?;
+    let default = default_branch(&repo);
+    let cutoff = chrono::Utc::now().timestamp() - (stale_days * 86_400) as i64;
+
+    let mut stale = Vec::new();
+    for branch in repo.branches(Some(BranchType::Local))? {
+        let (branch, _) = branch?;
+        let (Some(branch_name), Some(target)) = (branch.name()?, branch.get().target()) else {
+            continue;
+        };
+        // The default branch is merged into itself, and HEAD can't go
+        if branch.is_head() || default.as_ref().is_some_and(|(d, _)| d == branch_name) {
+            continue;
+        }
+        let commit = repo.find_commit(target)?;
+        let time = commit.time().seconds();
+        let candidate = StaleBranch {
+            repo: path.to_path_buf(),
+            repo_name: name.to_string(),
+            name: branch_name.to_string(),
+            target,
+            author: commit.author().name().unwrap_or_default().to_string(),
+            time,
+            summary: commit.summary().unwrap_or_default().to_string(),
+            merged: default
+                .as_ref()
+                .is_some_and(|(_, base)| is_merged(&repo, *base, target)),
+            gone: matches!(resolve_upstream(&repo, branch_name)?, Upstream::Gone { .. }),
+            idle: stale_days > 0 && time < cutoff,
+        };
+        if candidate.merged || candidate.gone || candidate.idle {
+            stale.push(candidate);
+        }
+    }
+    stale.sort_by_key(|b| b.time);
+    Ok(stale)
+}
+
+/// Blocking; reads every repo in `repos` one after another.
+pub fn find_stale(repos: &[PathBuf], config: &GitConfig) -> StaleReport {
+    let mut report = StaleReport::default();
+    for path in repos {
+        let name = repo_name(config, path);
+        match stale_in(path, &name, config.stale_days) {
+            Ok(branches) => report.branches.extend(branches),
+            Err(e) => report.failed.push((name, RepoError::from(e))),
+        }
+    }
+    report
+}
+
+/// Delete a branch unless it moved since the report was read, merged or not.
+fn delete(branch: &BranchRef) -> Result<(), git2::Error> {
+    let repo = Repository::open(&branch.repo)?;
+    let mut local = repo.find_branch(&branch.name, BranchType::Local)?;
+    if local.get().target() != Some(branch.target) {
+        return Err(git2::Error::from_str(&format!(
+            "{} has moved since the report was read",
+            branch.name
+        )));
+    }
+    if local.is_head() {
+        return Err(git2::Error::from_str(&format!(
+            "cannot delete {} while it is checked out",
+            branch.name
+        )));
+    }
+    local.delete()
+}
+
+/// Blocking; run through `GitRequest::DeleteBranches`.
+pub fn delete_branches(branches: Vec<BranchRef>) -> Vec<Deleted> {
+    branches
+        .into_iter()
+        .map(|branch| {
+            let result = delete(&branch).map_err(RepoError::from);
+            Deleted { branch, result }
+        })
+        .collect()
+}
+
+/// Append how to restore each deleted branch to
+/// `$XDG_DATA_HOME/ktop/deleted-branches.log`, so the commits can still be
+/// found after ktop exits. Returns the log's path.
+pub fn record(deleted: &[Deleted]) -> std::io::Result<PathBuf> {
+    let dir = data_home()?.join("ktop");
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join("deleted-branches.log");
+    let mut log = OpenOptions::new().create(true).append(true).open(&path)?;
+    let now = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
+    for d in deleted.iter().filter(|d| d.result.is_ok()) {
+        writeln!(log, "{now}  {}", d.restore_command())?;
+    }
+    Ok(path)
+}
@@ -0,0 +1,209 @@
+use std::path::Path;
+
+use git2::build::CheckoutBuilder;
+use git2::{DiffFormat, Oid, Repository, StashApplyOptions};
+
+use super::log::FileStat;
+
+/// Previews longer than this are cut off.
+const MAX_PREVIEW_LINES: usize = 5_000;
+
+#[derive(Debug, Clone)]
+pub struct StashEntry {
+    /// Position in the stash list, as in `stash@{N}`
+    pub index: usize,
+    pub id: Oid,
+    pub message: String,
+    /// Seconds since the epoch
+    pub time: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct StashPreview {
+    pub files: Vec<FileStat>,
+    /// Patch lines tagged with git's origin char: `+`, `-`, ` `, `F` for
+    /// file headers and `H` for hunk headers
+    pub lines: Vec<(char, String)>,
+    pub truncated: bool,
+}
+
+impl StashEntry {
+    pub fn key(&self) -> StashRef {
+        StashRef {
+            index: self.index,
+            id: self.id,
+        }
+    }
+}
+
+/// A stash entry as it was when the list was read. Indexes shift whenever
+/// something is stashed or dropped, so ops check `id` is still at `index`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StashRef {
+    pub index: usize,
+    pub id: Oid,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StashOp {
+    Apply(StashRef),
+    /// Apply, then drop unless the apply left conflicts
+    Pop(StashRef),
+    Drop(StashRef),
+}
+
+impl StashOp {
+    pub fn describe(&self) -> String {
+        match self {
+            StashOp::Apply(s) => format!("apply stash@{{{}}}", s.index),
+            StashOp::Pop(s) => format!("pop stash@{{{}}}", s.index),
+            StashOp::Drop(s) => format!("drop stash@{{{}}}", s.index),
+        }
+    }
+}
+
+/// Number of stash entries, read from the `refs/stash` reflog so it works
+/// without a mutable repository.
+pub fn count_stashes(repo: &Repository) -> usize {
+    // Reading a missing reflog creates an empty one
+    if repo.find_reference("refs/stash").is_err() {
+        return 0;
+    }
+    repo.reflog("refs/stash").map_or(0, |log| log.len())
+}
+
+pub fn list_stashes(path: &Path) -> Result<Vec<StashEntry>, git2::Error> {
+    let mut repo = Repository::open(path)?;
+    let mut stashes = Vec::new();
+    repo.stash_foreach(|index, message, id| {
+        stashes.push((index, message.to_string(), *id));
+        true
+    })?;
+
+    stashes
+        .into_iter()
+        .map(|(index, message, id)| {
+            Ok(StashEntry {
+                index,
+                id,
+                message,
+                time: repo.find_commit(id)?.time().seconds(),
+            })
+        })
+        .collect()
+}
+
+/// Changes to tracked files saved in the stash, against the commit it was
+/// made on.
+pub fn stash_preview(path: &Path, id: Oid) -> Result<StashPreview, git2::Error> {
+    let repo = Repository::open(path)?;
+    let stash = repo.find_commit(id)?;
+    let base = stash.parent(0)?;
+    let diff = repo.diff_tree_to_tree(Some(&base.tree()?), Some(&stash.tree()?), None)?;
+
+    let mut files = Vec::new();
+    for i in 0..diff.deltas().len() {
+        let Some(patch) = git2::Patch::from_diff(&diff, i)? else {
+            continue;
+        };
+        let (_, insertions, deletions) = patch.line_stats()?;
+        let delta = patch.delta();
+        let path = delta
+            .new_file()
+            .path()
+            .or(delta.old_file().path())
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default();
+        files.push(FileStat {
+            path,
+            insertions,
+            deletions,
+        });
+    }
+
+    let mut lines = Vec::new();
+    let mut truncated = false;
+    diff.print(DiffFormat::Patch, |_, _, line| {
+        if lines.len() >= MAX_PREVIEW_LINES {
+            truncated = true;
+            return false;
+        }
+        let text = String::from_utf8_lossy(line.content());
+        let text = text.trim_end_matches(['\n', '\r']).replace('\t', "    ");
+        match line.origin() {
+            // File headers arrive as one multi-line chunk
+            'F' => lines.extend(text.lines().map(|l| ('F', l.to_string()))),
+            origin @ ('+' | '-' | ' ' | 'H') => lines.push((origin, text)),
+            _ => {}
+        }
+        true
+    })
+    .or_else(|e| {
+        // Stopping early reports as a user abort
+        if truncated {
+            Ok(())
+        } else {
+            Err(e)
+        }
+    })?;
+
+    Ok(StashPreview {
+        files,
+        lines,
+        truncated,
+    })
+}
+
+fn conflicts(repo: &Repository) -> Result<usize, git2::Error> {
+    Ok(repo.index()?.conflicts()?.count())
+}
+
+/// Index of `stash` now, refusing it if another stash was pushed or dropped
+/// since the list was read.
+fn locate(repo: &mut Repository, stash: StashRef) -> Result<usize, git2::Error> {
+    let mut found = None;
+    repo.stash_foreach(|index, _, id| {
+        if *id == stash.id {
+            found = Some(index);
+        }
+        found.is_none()
+    })?;
+    match found {
+        Some(index) if index == stash.index => Ok(index),
+        Some(index) => Err(git2::Error::from_str(&format!(
+            "stash@{{{}}} is now stash@{{{index}}}; refresh the list and try again",
+            stash.index
+        ))),
+        None => Err(git2::Error::from_str(&format!(
+            "stash@{{{}}} no longer exists",
+            stash.index
+        ))),
+    }
+}
+
+/// Blocking; run through `GitRequest::Stash`. Returns how many paths were
+/// left conflicted.
+pub fn apply(path: &Path, op: StashOp) -> Result<usize, git2::Error> {
+    let mut repo = Repository::open(path)?;
+    match op {
+        StashOp::Apply(stash) | StashOp::Pop(stash) => {
+            let index = locate(&mut repo, stash)?;
+            let mut checkout = CheckoutBuilder::new();
+            checkout.allow_conflicts(true).conflict_style_merge(true);
+            let mut opts = StashApplyOptions::new();
+            opts.checkout_options(checkout);
+            repo.stash_apply(index, Some(&mut opts))?;
+
+            let conflicted = conflicts(&repo)?;
+            if conflicted == 0 && matches!(op, StashOp::Pop(_)) {
+                repo.stash_drop(index)?;
+            }
+            Ok(conflicted)
+        }
+        StashOp::Drop(stash) => {
+            let index = locate(&mut repo, stash)?;
+            repo.stash_drop(index)?;
+            Ok(0)
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::path::Path;
+
+use git2::{
+    FetchOptions, RemoteCallbacks, Repository, Submodule, SubmoduleIgnore, SubmoduleUpdateOptions,
+};
+
+use super::{count_changes, remote};
+use crate::config::StatusConfig;
+
+#[derive(Debug, Clone)]
+pub struct SubmoduleInfo {
+    pub name: String,
+    /// Relative to the superproject's worktree
+    pub path: String,
+    /// Short id of the commit the superproject's index records
+    pub recorded: Option<String>,
+    /// Short id of the commit checked out in the submodule
+    pub checked_out: Option<String>,
+    pub state: SubmoduleState,
+    pub modified: usize,
+    pub staged: usize,
+    pub untracked: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubmoduleState {
+    /// Listed in `.gitmodules` but never cloned or checked out
+    Uninitialized,
+    /// The checked-out commit isn't the one the superproject records
+    OutOfDate,
+    /// At the recorded commit, with changes inside
+    Modified,
+    Clean,
+}
+
+impl SubmoduleState {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SubmoduleState::Uninitialized => "uninitialized",
+            SubmoduleState::OutOfDate => "out of date",
+            SubmoduleState::Modified => "modified",
+            SubmoduleState::Clean => "",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubmoduleOp {
+    /// Clone if needed and check out the recorded commit
+    Update {
+        name: String,
+    },
+    UpdateAll,
+}
+
+impl SubmoduleOp {
+    pub fn describe(&self) -> String {
+        match self {
+            SubmoduleOp::Update { name } => format!("update submodule {name}"),
+            SubmoduleOp::UpdateAll => "update all submodules".to_string(),
+        }
+    }
+}
+
+fn short(id: Option<git2::Oid>) -> Option<String> {
+    id.map(|id| id.to_string()[..7].to_string())
+}
+
+fn submodule_info(
+    repo: &Repository,
+    submodule: &Submodule,
+    config: &StatusConfig,
+) -> Result<SubmoduleInfo, git2::Error> {
+    let name = submodule.name().unwrap_or_default().to_string();
+    let status = repo.submodule_status(&name, SubmoduleIgnore::None)?;
+    let mut info = SubmoduleInfo {
+        path: submodule.path().to_string_lossy().to_string(),
+        recorded: short(submodule.index_id().or(submodule.head_id())),
+        checked_out: short(submodule.workdir_id()),
+        state: SubmoduleState::Clean,
+        modified: 0,
+        staged: 0,
+        untracked: 0,
+        name,
+    };
+    if status.is_wd_uninitialized() || !status.is_in_wd() {
+        info.state = SubmoduleState::Uninitialized;
+        return Ok(info);
+    }
+    if let Ok(sub) = submodule.open() {
+        // The parent's pathspecs name its own paths, not the submodule's
+        let whole = StatusConfig {
+            pathspecs: Vec::new(),
+            ..config.clone()
+        };
+        let counts = count_changes(&sub, &whole)?;
+        info.modified = counts.modified;
+        info.staged = counts.staged;
+        info.untracked = counts.untracked;
+    }
+    info.state = if status.is_wd_modified() {
+        SubmoduleState::OutOfDate
+    } else if info.modified + info.staged + info.untracked > 0 {
+        SubmoduleState::Modified
+    } else {
+        SubmoduleState::Clean
+    };
+    Ok(info)
+}
+
+/// Submodules of `repo` in `.gitmodules` order. One that fails to read is
+/// left out rather than failing the whole repo.
+pub fn list_submodules(
+    repo: &Repository,
+    config: &StatusConfig,
+) -> Result<Vec<SubmoduleInfo>, git2::Error> {
+    if config.ignore_submodules {
+        return Ok(Vec::new());
+    }
+    Ok(repo
+        .submodules()?
+        .iter()
+        .filter_map(|submodule| submodule_info(repo, submodule, config).ok())
+        .collect())
+}
+
+/// Submodules of the repo at `path`, for the submodules view.
+pub fn read_submodules(
+    path: &Path,
+    config: &StatusConfig,
+) -> Result<Vec<SubmoduleInfo>, git2::Error> {
+    list_submodules(&Repository::open(path)?, config)
+}
+
+/// `git submodule update --init` for one submodule. Cloning and fetching
+/// authenticate like fetch and pull do.
+fn update(repo: &Repository, submodule: &mut Submodule) -> Result<(), git2::Error> {
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(remote::credentials(repo));
+    let mut fetch = FetchOptions::new();
+    fetch.remote_callbacks(callbacks);
+    let mut opts = SubmoduleUpdateOptions::new();
+    opts.fetch(fetch);
+    submodule.update(true, Some(&mut opts)).map_err(|e| {
+        let e = remote::explain(e);
+        git2::Error::new(
+            e.code(),
+            e.class(),
+            format!("{}: {}", submodule.name().unwrap_or_default(), e.message()),
+        )
+    })
+}
+
+/// Blocking; run through `GitRequest::Submodule`.
+pub fn apply(path: &Path, op: &SubmoduleOp) -> Result<(), git2::Error> {
+    let repo = Repository::open(path)?;
+    match op {
+        SubmoduleOp::Update { name } => update(&repo, &mut repo.find_submodule(name)?),
+        SubmoduleOp::UpdateAll => repo
+            .submodules()?
+            .iter_mut()
+            .try_for_each(|submodule| update(&repo, submodule)),
+    }
+}
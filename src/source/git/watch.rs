@@ -0,0 +1,204 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use git2::Repository;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+
+/// How long to wait for more changes before refreshing, so a save or a
+/// checkout touching many files refreshes once.
+const DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Filesystem watches on repo worktrees and git dirs, so a repo can be
+/// refreshed as soon as it changes instead of on the next poll.
+pub struct RepoWatcher {
+    /// `None` when watching is off or the watcher could not be created, in
+    /// which case repos are only polled
+    watches: Option<Watches>,
+    events: mpsc::UnboundedReceiver<PathBuf>,
+    /// Changed paths per repo, not yet handed out
+    pending: HashMap<PathBuf, Vec<PathBuf>>,
+}
+
+struct Watches {
+    watcher: RecommendedWatcher,
+    /// Watched directory and the repo it belongs to
+    roots: Vec<(PathBuf, PathBuf)>,
+    /// Repos that couldn't be watched (e.g. out of inotify watches); these
+    /// are only polled
+    failed: HashSet<PathBuf>,
+}
+
+impl RepoWatcher {
+    pub fn new(enabled: bool) -> Self {
+        let (tx, events) = mpsc::unbounded_channel();
+        let watches = enabled
+            .then(|| {
+                notify::recommended_watcher(move |res: notify::Result<Event>| match res {
+                    // Reads don't change anything
+                    Ok(event) if matches!(event.kind, EventKind::Access(_)) => {}
+                    Ok(event) => {
+                        for path in event.paths {
+                            let _ = tx.send(path);
+                        }
+                    }
+                    // Whatever was missed is picked up by the fallback poll
+                    Err(_) => {}
+                })
+                .ok()
+            })
+            .flatten()
+            .map(|watcher| Watches {
+                watcher,
+                roots: Vec::new(),
+                failed: HashSet::new(),
+            });
+
+        Self {
+            watches,
+            events,
+            pending: HashMap::new(),
+        }
+    }
+
+    pub fn is_watched(&self, repo: &Path) -> bool {
+        self.watches
+            .as_ref()
+            .is_some_and(|w| w.roots.iter().any(|(_, r)| r == repo))
+    }
+
+    /// Watch new repos and drop the watches of ones no longer listed.
+    /// Adding a recursive watch walks the whole tree, so this runs on the
+    /// blocking pool.
+    pub async fn sync(&mut self, repos: &[PathBuf]) {
+        let Some(mut watches) = self.watches.take() else {
+            return;
+        };
+        let repos = repos.to_vec();
+        // If the setup panicked, watching stops and everything is polled
+        self.watches = tokio::task::spawn_blocking(move || {
+            watches.sync(&repos);
+            watches
+        })
+        .await
+        .ok();
+    }
+
+    /// Changes `changed` hasn't handed out yet, for a poll to pick up.
+    pub fn take_pending(&mut self) -> HashMap<PathBuf, Vec<PathBuf>> {
+        while let Ok(path) = self.events.try_recv() {
+            self.record(path);
+        }
+        std::mem::take(&mut self.pending)
+    }
+
+    /// Wait for a change, then for things to settle, and return the changed
+    /// paths per repo. Never returns when nothing is watched. Safe to
+    /// cancel: changes seen so far are kept for the next call.
+    pub async fn changed(&mut self) -> HashMap<PathBuf, Vec<PathBuf>> {
+        if self.watches.is_none() {
+            return std::future::pending().await;
+        }
+        while self.pending.is_empty() {
+            match self.events.recv().await {
+                Some(path) => self.record(path),
+                None => return std::future::pending().await,
+            }
+        }
+
+        let settle = tokio::time::sleep(DEBOUNCE);
+        tokio::pin!(settle);
+        loop {
+            tokio::select! {
+                _ = &mut settle => break,
+                Some(path) = self.events.recv() => self.record(path),
+            }
+        }
+        std::mem::take(&mut self.pending)
+    }
+
+    /// File the path under the repo with the closest watched directory.
+    fn record(&mut self, path: PathBuf) {
+        let Some(watches) = &self.watches else {
+            return;
+        };
+        let repo = watches
+            .roots
+            .iter()
+            .filter(|(root, _)| path.starts_with(root))
+            .max_by_key(|(root, _)| root.as_os_str().len())
+            .map(|(_, repo)| repo.clone());
+        if let Some(repo) = repo {
+            self.pending.entry(repo).or_default().push(path);
+        }
+    }
+}
+
+impl Watches {
+    fn sync(&mut self, repos: &[PathBuf]) {
+        let watcher = &mut self.watcher;
+        self.roots.retain(|(root, repo)| {
+            let keep = repos.contains(repo);
+            if !keep {
+                let _ = watcher.unwatch(root);
+            }
+            keep
+        });
+        self.failed.retain(|repo| repos.contains(repo));
+
+        for repo in repos {
+            if self.failed.contains(repo) || self.roots.iter().any(|(_, r)| r == repo) {
+                continue;
+            }
+            // Repos that don't open are polled and retried next time
+            let Ok(roots) = watch_roots(repo) else {
+                continue;
+            };
+            let mut added: Vec<PathBuf> = Vec::new();
+            for root in roots {
+                if self.watcher.watch(&root, RecursiveMode::Recursive).is_err() {
+                    for root in &added {
+                        let _ = self.watcher.unwatch(root);
+                    }
+                    self.failed.insert(repo.clone());
+                    added.clear();
+                    break;
+                }
+                added.push(root);
+            }
+            self.roots
+                .extend(added.into_iter().map(|root| (root, repo.clone())));
+        }
+    }
+}
+
+/// The worktree, plus the git dir when it lives elsewhere (linked worktrees,
+/// `--separate-git-dir`, bare repos).
+fn watch_roots(repo: &Path) -> Result<Vec<PathBuf>, git2::Error> {
+    let repo = Repository::open(repo)?;
+    let gitdir = repo.path().to_path_buf();
+    Ok(match repo.workdir() {
+        Some(workdir) if gitdir.starts_with(workdir) => vec![workdir.to_path_buf()],
+        Some(workdir) => vec![workdir.to_path_buf(), gitdir],
+        None => vec![gitdir],
+    })
+}
+
+/// Whether any of `paths` can change what the table shows: anything in the
+/// git dir except new objects, or a worktree file git doesn't ignore.
+/// Blocking.
+pub fn is_relevant(repo: &Path, paths: &[PathBuf]) -> bool {
+    let Ok(repo) = Repository::open(repo) else {
+        return true;
+    };
+    paths.iter().any(|path| {
+        if let Ok(rel) = path.strip_prefix(repo.path()) {
+            return !rel.starts_with("objects");
+        }
+        match repo.workdir().and_then(|w| path.strip_prefix(w).ok()) {
+            Some(rel) => !repo.is_path_ignored(rel).unwrap_or(false),
+            None => true,
+        }
+    })
+}
@@ -0,0 +1,148 @@
+use std::path::{Path, PathBuf};
+
+use git2::{
+    BranchType, Repository, Worktree, WorktreeAddOptions, WorktreeLockStatus, WorktreePruneOptions,
+};
+
+use super::{count_changes, resolve_head, HeadState};
+use crate::config::StatusConfig;
+
+#[derive(Debug, Clone)]
+pub struct WorktreeInfo {
+    pub name: String,
+    pub path: PathBuf,
+    /// `None` when the worktree can't be opened, e.g. its directory is gone
+    pub head: Option<HeadState>,
+    pub modified: usize,
+    pub staged: usize,
+    pub untracked: usize,
+    /// Lock reason, empty when locked without one
+    pub locked: Option<String>,
+    /// Its directory is gone and it isn't locked, so pruning would drop it
+    pub prunable: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorktreeOp {
+    /// Check out a branch named after the directory at `path`, creating the
+    /// branch from HEAD if it doesn't exist
+    Add { path: PathBuf },
+    /// Forget a worktree whose directory is gone
+    Prune { name: String },
+}
+
+impl WorktreeOp {
+    pub fn describe(&self) -> String {
+        match self {
+            WorktreeOp::Add { path } => format!("add worktree {}", path.display()),
+            WorktreeOp::Prune { name } => format!("prune worktree {name}"),
+        }
+    }
+}
+
+fn worktree_info(worktree: &Worktree, config: &StatusConfig) -> Result<WorktreeInfo, git2::Error> {
+    let mut info = WorktreeInfo {
+        name: worktree.name().unwrap_or_default().to_string(),
+        path: worktree.path().to_path_buf(),
+        head: None,
+        modified: 0,
+        staged: 0,
+        untracked: 0,
+        locked: match worktree.is_locked()? {
+            WorktreeLockStatus::Unlocked => None,
+            WorktreeLockStatus::Locked(reason) => Some(reason.unwrap_or_default()),
+        },
+        prunable: worktree.is_prunable(None)?,
+    };
+    if worktree.validate().is_ok() {
+        if let Ok(repo) = Repository::open_from_worktree(worktree) {
+            let counts = count_changes(&repo, config)?;
+            info.head = Some(resolve_head(&repo)?);
+            info.modified = counts.modified;
+            info.staged = counts.staged;
+            info.untracked = counts.untracked;
+        }
+    }
+    Ok(info)
+}
+
+/// Linked worktrees of `repo`, sorted by name. One that fails to read is
+/// left out rather than failing the whole repo.
+pub fn list_worktrees(
+    repo: &Repository,
+    config: &StatusConfig,
+) -> Result<Vec<WorktreeInfo>, git2::Error> {
+    let names = repo.worktrees()?;
+    let mut worktrees: Vec<WorktreeInfo> = names
+        .iter()
+        .flatten()
+        .filter_map(|name| {
+            let worktree = repo.find_worktree(name).ok()?;
+            worktree_info(&worktree, config).ok()
+        })
+        .collect();
+    worktrees.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(worktrees)
+}
+
+/// Worktrees of the repo at `path`, for the worktrees view.
+pub fn read_worktrees(
+    path: &Path,
+    config: &StatusConfig,
+) -> Result<Vec<WorktreeInfo>, git2::Error> {
+    list_worktrees(&Repository::open(path)?, config)
+}
+
+fn add(repo: &Repository, path: &Path) -> Result<(), git2::Error> {
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| git2::Error::from_str(&format!("{} has no usable name", path.display())))?;
+    if path.exists() {
+        return Err(git2::Error::from_str(&format!(
+            "{} already exists",
+            path.display()
+        )));
+    }
+
+    let mut opts = WorktreeAddOptions::new();
+    // libgit2 creates a branch named after the worktree unless given one
+    let existing = repo.find_branch(name, BranchType::Local).ok();
+    if let Some(branch) = &existing {
+        if branch.is_head() {
+            return Err(git2::Error::from_str(&format!(
+                "{name} is checked out in the main worktree"
+            )));
+        }
+        opts.reference(Some(branch.get()));
+    }
+    repo.worktree(name, path, Some(&opts))?;
+    Ok(())
+}
+
+fn prune(repo: &Repository, name: &str) -> Result<(), git2::Error> {
+    let worktree = repo.find_worktree(name)?;
+    if let WorktreeLockStatus::Locked(reason) = worktree.is_locked()? {
+        let reason = reason
+            .filter(|r| !r.is_empty())
+            .unwrap_or("no reason given".to_string());
+        return Err(git2::Error::from_str(&format!(
+            "{name} is locked ({reason}); unlock it first"
+        )));
+    }
+    if !worktree.is_prunable(None)? {
+        return Err(git2::Error::from_str(&format!(
+            "{name} is still in use; remove its directory first"
+        )));
+    }
+    worktree.prune(Some(&mut WorktreePruneOptions::new()))
+}
+
+/// Blocking; run through `GitRequest::Worktree`.
+pub fn apply(path: &Path, op: &WorktreeOp) -> Result<(), git2::Error> {
+    let repo = Repository::open(path)?;
+    match op {
+        WorktreeOp::Add { path } => add(&repo, path),
+        WorktreeOp::Prune { name } => prune(&repo, name),
+    }
+}
@@ -0,0 +1,253 @@
+use std::collections::HashSet;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone)]
+pub struct HoldersResult {
+    pub query: PathBuf,
+    pub report: std::result::Result<HoldersReport, String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HoldersReport {
+    pub holders: Vec<Holder>,
+    pub locks: Vec<FileLock>,
+    /// Processes whose `/proc` entries could not be read (usually EACCES).
+    pub skipped: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Holder {
+    pub pid: u32,
+    pub name: String,
+    pub kind: HoldKind,
+    pub target: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoldKind {
+    Fd(u32),
+    Cwd,
+    Root,
+    Exe,
+    Mmap,
+}
+
+#[derive(Debug, Clone)]
+pub struct FileLock {
+    pub pid: u32,
+    pub name: String,
+    /// POSIX, FLOCK, OFDLCK, LEASE...
+    pub class: String,
+    /// READ or WRITE
+    pub mode: String,
+    /// Blocked waiter rather than a holder (`->` entries in /proc/locks).
+    pub waiting: bool,
+    pub target: String,
+}
+
+impl HoldKind {
+    pub fn label(&self) -> String {
+        match self {
+            HoldKind::Fd(fd) => format!("fd {fd}"),
+            HoldKind::Cwd => "cwd".to_string(),
+            HoldKind::Root => "root".to_string(),
+            HoldKind::Exe => "exe".to_string(),
+            HoldKind::Mmap => "mmap".to_string(),
+        }
+    }
+}
+
+/// `(major, minor, inode)` as written in /proc/locks.
+type FileId = (u64, u64, u64);
+
+fn file_id(meta: &fs::Metadata) -> FileId {
+    let dev = meta.dev();
+    let major = ((dev >> 8) & 0xfff) | ((dev >> 32) & !0xfff);
+    let minor = (dev & 0xff) | ((dev >> 12) & !0xff);
+    (major, minor, meta.ino())
+}
+
+/// Strip the ` (deleted)` suffix the kernel appends to unlinked targets.
+fn clean_target(target: &str) -> &str {
+    target.strip_suffix(" (deleted)").unwrap_or(target)
+}
+
+fn process_name(pid: u32) -> String {
+    fs::read_to_string(format!("/proc/{pid}/comm"))
+        .map(|s| s.trim().to_string())
+        .unwrap_or_default()
+}
+
+/// Scan every process in /proc for open fds, cwd, root, exe and memory
+/// mappings under `query`, plus any file locks taken on those files.
+pub fn find_holders(query: &Path) -> std::io::Result<HoldersReport> {
+    let query = fs::canonicalize(query)?;
+    let mut report = HoldersReport::default();
+    let mut held_files: HashSet<FileId> = HashSet::new();
+
+    for entry in fs::read_dir("/proc")? {
+        let Ok(entry) = entry else { continue };
+        let Some(pid) = entry
+            .file_name()
+            .to_str()
+            .and_then(|s| s.parse::<u32>().ok())
+        else {
+            continue;
+        };
+
+        let before = report.holders.len();
+        if scan_process(pid, &query, &mut report.holders, &mut held_files).is_err() {
+            report.skipped += 1;
+        }
+        if report.holders.len() > before {
+            let name = process_name(pid);
+            for holder in &mut report.holders[before..] {
+                holder.name = name.clone();
+            }
+        }
+    }
+
+    // Include the files themselves so locks on a file nobody has open under
+    // a different path are still matched.
+    if let Ok(meta) = fs::metadata(&query) {
+        held_files.insert(file_id(&meta));
+    }
+    report.locks = read_locks(&held_files, &query);
+
+    report.holders.sort_by_key(|h| h.pid);
+    Ok(report)
+}
+
+fn scan_process(
+    pid: u32,
+    query: &Path,
+    holders: &mut Vec<Holder>,
+    held_files: &mut HashSet<FileId>,
+) -> std::io::Result<()> {
+    let proc_dir = PathBuf::from(format!("/proc/{pid}"));
+    let mut push = |kind: HoldKind, target: String| {
+        holders.push(Holder {
+            pid,
+            name: String::new(),
+            kind,
+            target,
+        })
+    };
+
+    for (link, kind) in [
+        ("cwd", HoldKind::Cwd),
+        ("root", HoldKind::Root),
+        ("exe", HoldKind::Exe),
+    ] {
+        if let Ok(target) = fs::read_link(proc_dir.join(link)) {
+            let target = target.to_string_lossy();
+            // Every process has `/` as root; only report it for chroots.
+            if kind == HoldKind::Root && target == "/" && query != Path::new("/") {
+                continue;
+            }
+            if Path::new(clean_target(&target)).starts_with(query) {
+                push(kind, target.to_string());
+            }
+        }
+    }
+
+    for fd_entry in fs::read_dir(proc_dir.join("fd"))? {
+        let Ok(fd_entry) = fd_entry else { continue };
+        let Some(fd) = fd_entry
+            .file_name()
+            .to_str()
+            .and_then(|s| s.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        let Ok(target) = fs::read_link(fd_entry.path()) else {
+            continue;
+        };
+        let target = target.to_string_lossy();
+        if Path::new(clean_target(&target)).starts_with(query) {
+            if let Ok(meta) = fs::metadata(fd_entry.path()) {
+                held_files.insert(file_id(&meta));
+            }
+            push(HoldKind::Fd(fd), target.to_string());
+        }
+    }
+
+    if let Ok(maps) = fs::read_to_string(proc_dir.join("maps")) {
+        let mut seen: HashSet<&str> = HashSet::new();
+        for line in maps.lines() {
+            let Some(path) = line.splitn(6, char::is_whitespace).nth(5).map(str::trim) else {
+                continue;
+            };
+            if path.starts_with('/')
+                && Path::new(clean_target(path)).starts_with(query)
+                && seen.insert(path)
+            {
+                push(HoldKind::Mmap, path.to_string());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse /proc/locks, keeping entries whose file is in `held_files`.
+///
+/// Lines look like `1: POSIX  ADVISORY  WRITE 1234 08:01:5678 0 EOF`, with
+/// blocked waiters written as `1: -> POSIX ...`.
+fn read_locks(held_files: &HashSet<FileId>, query: &Path) -> Vec<FileLock> {
+    let Ok(content) = fs::read_to_string("/proc/locks") else {
+        return vec![];
+    };
+
+    let mut locks = Vec::new();
+    for line in content.lines() {
+        let mut fields: Vec<&str> = line.split_whitespace().skip(1).collect();
+        let waiting = fields.first() == Some(&"->");
+        if waiting {
+            fields.remove(0);
+        }
+        // class, ADVISORY/MANDATORY, mode, pid, dev:inode, start, end
+        if fields.len() < 5 {
+            continue;
+        }
+        let Ok(pid) = fields[3].parse::<u32>() else {
+            continue;
+        };
+        let Some(id) = parse_lock_file_id(fields[4]) else {
+            continue;
+        };
+        if !held_files.contains(&id) {
+            continue;
+        }
+
+        locks.push(FileLock {
+            pid,
+            name: process_name(pid),
+            class: fields[0].to_string(),
+            mode: fields[2].to_string(),
+            waiting,
+            target: lock_target(pid, id).unwrap_or_else(|| query.to_string_lossy().to_string()),
+        });
+    }
+    locks
+}
+
+fn parse_lock_file_id(field: &str) -> Option<FileId> {
+    let mut parts = field.split(':');
+    let major = u64::from_str_radix(parts.next()?, 16).ok()?;
+    let minor = u64::from_str_radix(parts.next()?, 16).ok()?;
+    let inode = parts.next()?.parse::<u64>().ok()?;
+    Some((major, minor, inode))
+}
+
+/// Find the path the lock holder has the locked inode open under.
+fn lock_target(pid: u32, id: FileId) -> Option<String> {
+    fs::read_dir(format!("/proc/{pid}/fd"))
+        .ok()?
+        .flatten()
+        .find(|fd| fs::metadata(fd.path()).is_ok_and(|m| file_id(&m) == id))
+        .and_then(|fd| fs::read_link(fd.path()).ok())
+        .map(|p| p.to_string_lossy().to_string())
+}
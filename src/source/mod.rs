@@ -1,7 +1,14 @@
+pub mod du;
 pub mod git;
+pub mod holders;
+pub mod netstat;
+pub mod process;
+pub mod smaps;
 pub mod system;
 
 use async_trait::async_trait;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::path::PathBuf;
 use std::time::Duration;
 use tokio::sync::mpsc;
 
@@ -11,6 +18,31 @@ use crate::errors::Result;
 pub enum DataSnapshot {
     System(system::SystemSnapshot),
     Git(git::GitSnapshot),
+    /// One repo's row, read after the snapshot it belongs to was sent
+    GitRepo(Box<git::RepoEntry>),
+    GitEvent(git::GitEvent),
+    Process(process::ProcessSnapshot),
+    Smaps(smaps::SmapsResult),
+    Holders(holders::HoldersResult),
+    Du(du::DuEvent),
+    NetStat(netstat::NetStatSnapshot),
+}
+
+/// Compile exclude/ignore patterns from the config into one matcher.
+pub fn build_globset(patterns: &[String]) -> std::result::Result<GlobSet, String> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern).map_err(|e| e.to_string())?);
+    }
+    builder.build().map_err(|e| e.to_string())
+}
+
+/// `$XDG_DATA_HOME`, or `~/.local/share` when it isn't set.
+pub fn data_home() -> std::io::Result<PathBuf> {
+    std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".local/share")))
+        .ok_or_else(|| std::io::Error::other("neither XDG_DATA_HOME nor HOME is set"))
 }
 
 #[async_trait]
@@ -39,28 +71,33 @@ pub trait DataSource: Send + 'static {
     }
 }
 
-pub fn spawn_sources(
-    config: &crate::config::Config,
-) -> mpsc::UnboundedReceiver<DataSnapshot> {
-    let (tx, rx) = mpsc::unbounded_channel();
-
+pub fn spawn_sources(config: &crate::config::Config, tx: mpsc::UnboundedSender<DataSnapshot>) {
     // Spawn system source
-    let sys_source = system::SystemSource::new();
+    let sys_source = system::SystemSource::new(std::time::Duration::from_secs(
+        config.disk.forecast_window_mins * 60,
+    ));
     let sys_tx = tx.clone();
     tokio::spawn(async move {
         sys_source.run(sys_tx).await;
     });
 
-    // Spawn git source
-    let repo_paths: Vec<std::path::PathBuf> = config
-        .git
-        .repos
-        .iter()
-        .map(std::path::PathBuf::from)
-        .collect();
+    // Spawn process source
+    let proc_source = process::ProcessSource::new();
+    let proc_tx = tx.clone();
+    tokio::spawn(async move {
+        proc_source.run(proc_tx).await;
+    });
 
-    if !repo_paths.is_empty() {
-        let git_source = git::GitSource::new(repo_paths, config.git.interval_secs);
+    // Spawn TCP stack counter source
+    let net_source = netstat::NetStatSource::new();
+    let net_tx = tx.clone();
+    tokio::spawn(async move {
+        net_source.run(net_tx).await;
+    });
+
+    // Spawn git source
+    if !config.git.repos.is_empty() || !config.git.scan_roots.is_empty() {
+        let git_source = git::GitSource::new(&config.git);
         let git_tx = tx.clone();
         tokio::spawn(async move {
             git_source.run(git_tx).await;
@@ -68,6 +105,4 @@ pub fn spawn_sources(
     }
 
     let _ = tx;
-
-    rx
 }
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+use std::fs;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+
+use super::{DataSnapshot, DataSource};
+use crate::errors::Result;
+
+/// Counters shown in the Network tab: `(file section, field, label, alarming)`.
+/// Alarming counters are highlighted whenever their rate is non-zero.
+const COUNTERS: &[(&str, &str, &str, bool)] = &[
+    ("TcpExt", "ListenOverflows", "Accept queue overflows", true),
+    ("TcpExt", "ListenDrops", "Listen drops", true),
+    ("TcpExt", "TCPReqQFullDrop", "SYN queue full drops", true),
+    ("TcpExt", "SyncookiesSent", "SYN cookies sent", true),
+    ("TcpExt", "TCPBacklogDrop", "Socket backlog drops", true),
+    (
+        "TcpExt",
+        "TCPAbortOnMemory",
+        "Aborts on memory pressure",
+        true,
+    ),
+    ("Tcp", "RetransSegs", "Retransmitted segments", false),
+    ("TcpExt", "TCPSynRetrans", "SYN retransmits", false),
+    ("TcpExt", "TCPTimeouts", "Retransmit timeouts", false),
+    ("Tcp", "AttemptFails", "Failed connection attempts", false),
+    ("Tcp", "EstabResets", "Established resets", false),
+    ("Tcp", "InErrs", "Segments received in error", false),
+    ("Tcp", "ActiveOpens", "Active opens", false),
+    ("Tcp", "PassiveOpens", "Passive opens", false),
+    ("Tcp", "InSegs", "Segments in", false),
+    ("Tcp", "OutSegs", "Segments out", false),
+];
+
+#[derive(Debug, Clone, Default)]
+pub struct NetStatSnapshot {
+    pub counters: Vec<CounterRate>,
+    pub sockets: SocketStats,
+}
+
+#[derive(Debug, Clone)]
+pub struct CounterRate {
+    pub label: &'static str,
+    pub alarming: bool,
+    pub total: u64,
+    /// Events per second since the previous sample.
+    pub rate: f64,
+}
+
+/// Gauges from /proc/net/sockstat and the tcp_mem sysctl.
+#[derive(Debug, Clone, Default)]
+pub struct SocketStats {
+    pub sockets_used: u64,
+    pub tcp_inuse: u64,
+    pub tcp_orphan: u64,
+    pub tcp_time_wait: u64,
+    pub tcp_alloc: u64,
+    pub tcp_mem_bytes: u64,
+    /// tcp_mem pressure threshold, in bytes
+    pub tcp_mem_pressure: u64,
+    /// tcp_mem hard limit, in bytes
+    pub tcp_mem_max: u64,
+    pub udp_inuse: u64,
+    pub udp_mem_bytes: u64,
+}
+
+pub struct NetStatSource {
+    previous: Option<(Instant, HashMap<String, u64>)>,
+    page_size: u64,
+}
+
+impl NetStatSource {
+    pub fn new() -> Self {
+        // SAFETY: sysconf has no preconditions
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+        Self {
+            previous: None,
+            page_size: u64::try_from(page_size).unwrap_or(4096),
+        }
+    }
+}
+
+/// Parse the paired header/value line format shared by /proc/net/snmp and
+/// /proc/net/netstat into `"Section.Field" -> value`.
+fn parse_paired_lines(content: &str, out: &mut HashMap<String, u64>) {
+    let mut lines = content.lines();
+    while let (Some(header), Some(values)) = (lines.next(), lines.next()) {
+        let mut names = header.split_whitespace();
+        let mut vals = values.split_whitespace();
+        let (Some(section), Some(_)) = (names.next(), vals.next()) else {
+            continue;
+        };
+        let section = section.trim_end_matches(':');
+        for (name, value) in names.zip(vals) {
+            if let Ok(v) = value.parse::<u64>() {
+                out.insert(format!("{section}.{name}"), v);
+            }
+        }
+    }
+}
+
+/// Parse /proc/net/sockstat lines like `TCP: inuse 4 orphan 0 tw 0 alloc 4 mem 0`.
+fn parse_sockstat(content: &str) -> HashMap<String, u64> {
+    let mut out = HashMap::new();
+    for line in content.lines() {
+        let Some((section, rest)) = line.split_once(':') else {
+            continue;
+        };
+        let fields: Vec<&str> = rest.split_whitespace().collect();
+        for pair in fields.chunks(2) {
+            if let [name, value] = pair {
+                if let Ok(v) = value.parse::<u64>() {
+                    out.insert(format!("{section}.{name}"), v);
+                }
+            }
+        }
+    }
+    out
+}
+
+impl NetStatSource {
+    fn read_socket_stats(&self) -> SocketStats {
+        let sockstat = fs::read_to_string("/proc/net/sockstat")
+            .map(|c| parse_sockstat(&c))
+            .unwrap_or_default();
+        let get = |key: &str| sockstat.get(key).copied().unwrap_or(0);
+
+        // tcp_mem is "min pressure max", in pages
+        let tcp_mem: Vec<u64> = fs::read_to_string("/proc/sys/net/ipv4/tcp_mem")
+            .map(|c| {
+                c.split_whitespace()
+                    .filter_map(|v| v.parse().ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        SocketStats {
+            sockets_used: get("sockets.used"),
+            tcp_inuse: get("TCP.inuse"),
+            tcp_orphan: get("TCP.orphan"),
+            tcp_time_wait: get("TCP.tw"),
+            tcp_alloc: get("TCP.alloc"),
+            tcp_mem_bytes: get("TCP.mem") * self.page_size,
+            tcp_mem_pressure: tcp_mem.get(1).copied().unwrap_or(0) * self.page_size,
+            tcp_mem_max: tcp_mem.get(2).copied().unwrap_or(0) * self.page_size,
+            udp_inuse: get("UDP.inuse"),
+            udp_mem_bytes: get("UDP.mem") * self.page_size,
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for NetStatSource {
+    async fn collect(&mut self) -> Result<DataSnapshot> {
+        let now = Instant::now();
+        let mut values = HashMap::new();
+        // Either file may be missing inside some containers or without IPv4;
+        // whatever counters are found are still shown
+        for file in ["/proc/net/snmp", "/proc/net/netstat"] {
+            if let Ok(content) = fs::read_to_string(file) {
+                parse_paired_lines(&content, &mut values);
+            }
+        }
+
+        let counters = COUNTERS
+            .iter()
+            .filter_map(|(section, field, label, alarming)| {
+                let key = format!("{section}.{field}");
+                let total = *values.get(&key)?;
+                let rate = match &self.previous {
+                    Some((then, prev)) => {
+                        let elapsed = now.duration_since(*then).as_secs_f64();
+                        let delta = total.saturating_sub(prev.get(&key).copied().unwrap_or(total));
+                        if elapsed > 0.0 {
+                            delta as f64 / elapsed
+                        } else {
+                            0.0
+                        }
+                    }
+                    None => 0.0,
+                };
+                Some(CounterRate {
+                    label,
+                    alarming: *alarming,
+                    total,
+                    rate,
+                })
+            })
+            .collect();
+
+        self.previous = Some((now, values));
+
+        Ok(DataSnapshot::NetStat(NetStatSnapshot {
+            counters,
+            sockets: self.read_socket_stats(),
+        }))
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(1)
+    }
+}
@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use sysinfo::{ProcessesToUpdate, System};
+
+use super::{DataSnapshot, DataSource};
+use crate::errors::Result;
+
+#[derive(Debug, Clone, Default)]
+pub struct ProcessSnapshot {
+    pub processes: Vec<ProcessInfo>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub name: String,
+    pub cpu_usage: f32,
+    pub memory: u64,
+}
+
+pub struct ProcessSource {
+    sys: System,
+}
+
+impl ProcessSource {
+    pub fn new() -> Self {
+        let mut sys = System::new();
+        sys.refresh_processes(ProcessesToUpdate::All, true);
+        Self { sys }
+    }
+}
+
+#[async_trait]
+impl DataSource for ProcessSource {
+    async fn collect(&mut self) -> Result<DataSnapshot> {
+        self.sys.refresh_processes(ProcessesToUpdate::All, true);
+
+        let mut processes: Vec<ProcessInfo> = self
+            .sys
+            .processes()
+            .values()
+            // sysinfo lists threads alongside processes on Linux
+            .filter(|p| p.thread_kind().is_none())
+            .map(|p| ProcessInfo {
+                pid: p.pid().as_u32(),
+                name: p.name().to_string_lossy().to_string(),
+                cpu_usage: p.cpu_usage(),
+                memory: p.memory(),
+            })
+            .collect();
+
+        processes.sort_by(|a, b| b.memory.cmp(&a.memory).then(a.pid.cmp(&b.pid)));
+
+        Ok(DataSnapshot::Process(ProcessSnapshot { processes }))
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(2)
+    }
+}
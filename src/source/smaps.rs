@@ -0,0 +1,186 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+const LARGEST_MAPPINGS: usize = 20;
+
+#[derive(Debug, Clone)]
+pub struct SmapsResult {
+    pub pid: u32,
+    pub report: std::result::Result<SmapsReport, String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SmapsReport {
+    pub total: MemUsage,
+    pub by_kind: Vec<(MappingKind, MemUsage)>,
+    pub largest: Vec<Mapping>,
+}
+
+/// Memory accounting for one or more mappings, in bytes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MemUsage {
+    pub rss: u64,
+    pub pss: u64,
+    pub uss: u64,
+    pub swap: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MappingKind {
+    Heap,
+    Stack,
+    Anonymous,
+    FileBacked,
+    SharedLib,
+    Other,
+}
+
+#[derive(Debug, Clone)]
+pub struct Mapping {
+    pub range: String,
+    pub perms: String,
+    pub path: String,
+    pub kind: MappingKind,
+    pub usage: MemUsage,
+}
+
+impl MemUsage {
+    fn add(&mut self, other: &MemUsage) {
+        self.rss += other.rss;
+        self.pss += other.pss;
+        self.uss += other.uss;
+        self.swap += other.swap;
+    }
+
+    fn apply_field(&mut self, key: &str, kb: u64) {
+        let bytes = kb * 1024;
+        match key {
+            "Rss" => self.rss = bytes,
+            "Pss" => self.pss = bytes,
+            "Private_Clean" | "Private_Dirty" => self.uss += bytes,
+            "Swap" => self.swap = bytes,
+            _ => {}
+        }
+    }
+}
+
+impl MappingKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            MappingKind::Heap => "Heap",
+            MappingKind::Stack => "Stack",
+            MappingKind::Anonymous => "Anonymous",
+            MappingKind::FileBacked => "File-backed",
+            MappingKind::SharedLib => "Shared libs",
+            MappingKind::Other => "Other",
+        }
+    }
+
+    fn classify(path: &str) -> Self {
+        if path == "[heap]" {
+            MappingKind::Heap
+        } else if path.starts_with("[stack") {
+            MappingKind::Stack
+        } else if path.is_empty() || path.starts_with("[anon") {
+            MappingKind::Anonymous
+        } else if path.starts_with('[') {
+            MappingKind::Other
+        } else if is_shared_lib(path) {
+            MappingKind::SharedLib
+        } else {
+            MappingKind::FileBacked
+        }
+    }
+}
+
+fn is_shared_lib(path: &str) -> bool {
+    let file = path.rsplit('/').next().unwrap_or(path);
+    file.ends_with(".so") || file.contains(".so.")
+}
+
+/// Read `/proc/<pid>/smaps_rollup` for totals and `/proc/<pid>/smaps` for the
+/// per-mapping breakdown.
+pub fn read_smaps(pid: u32) -> std::io::Result<SmapsReport> {
+    let smaps = fs::read_to_string(format!("/proc/{pid}/smaps"))?;
+    let mappings = parse_smaps(&smaps);
+
+    // smaps_rollup is cheaper for the kernel to produce and exists since 4.14;
+    // fall back to summing the mappings on older kernels.
+    let total = match fs::read_to_string(format!("/proc/{pid}/smaps_rollup")) {
+        Ok(rollup) => parse_smaps(&rollup)
+            .first()
+            .map(|m| m.usage)
+            .unwrap_or_default(),
+        Err(_) => mappings.iter().fold(MemUsage::default(), |mut acc, m| {
+            acc.add(&m.usage);
+            acc
+        }),
+    };
+
+    let mut by_kind: BTreeMap<MappingKind, MemUsage> = BTreeMap::new();
+    for m in &mappings {
+        by_kind.entry(m.kind).or_default().add(&m.usage);
+    }
+
+    let mut largest: Vec<Mapping> = mappings.into_iter().filter(|m| m.usage.rss > 0).collect();
+    largest.sort_by_key(|m| std::cmp::Reverse(m.usage.pss));
+    largest.truncate(LARGEST_MAPPINGS);
+
+    Ok(SmapsReport {
+        total,
+        by_kind: by_kind.into_iter().collect(),
+        largest,
+    })
+}
+
+fn parse_smaps(content: &str) -> Vec<Mapping> {
+    let mut mappings: Vec<Mapping> = Vec::new();
+
+    for line in content.lines() {
+        if let Some(header) = parse_header(line) {
+            mappings.push(header);
+            continue;
+        }
+
+        let Some((key, rest)) = line.split_once(':') else {
+            continue;
+        };
+        let Some(current) = mappings.last_mut() else {
+            continue;
+        };
+        let kb = rest
+            .trim()
+            .trim_end_matches("kB")
+            .trim()
+            .parse::<u64>()
+            .unwrap_or(0);
+        current.usage.apply_field(key, kb);
+    }
+
+    mappings
+}
+
+/// Mapping headers look like `7f12a000-7f12b000 r-xp 00000000 08:01 1234  /usr/lib/libc.so.6`.
+fn parse_header(line: &str) -> Option<Mapping> {
+    let mut parts = line.splitn(6, char::is_whitespace);
+    let range = parts.next()?;
+    let (start, end) = range.split_once('-')?;
+    if !start.chars().all(|c| c.is_ascii_hexdigit()) || !end.chars().all(|c| c.is_ascii_hexdigit())
+    {
+        return None;
+    }
+
+    let perms = parts.next()?.to_string();
+    let _offset = parts.next()?;
+    let _dev = parts.next()?;
+    let _inode = parts.next()?;
+    let path = parts.next().unwrap_or("").trim().to_string();
+
+    Some(Mapping {
+        range: range.to_string(),
+        perms,
+        kind: MappingKind::classify(&path),
+        path,
+        usage: MemUsage::default(),
+    })
+}
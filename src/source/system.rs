@@ -1,4 +1,5 @@
-use std::time::Duration;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use sysinfo::{Disks, System};
@@ -6,6 +7,12 @@ use sysinfo::{Disks, System};
 use super::{DataSnapshot, DataSource};
 use crate::errors::Result;
 
+/// Minimum span of history before a trend is considered meaningful.
+const MIN_FORECAST_SPAN: Duration = Duration::from_secs(60);
+/// Forecasts further out than this come from a nearly flat trend and are
+/// reported as stable.
+const MAX_FORECAST: Duration = Duration::from_secs(365 * 24 * 3600);
+
 #[derive(Debug, Clone, Default)]
 pub struct SystemSnapshot {
     pub cpu_usages: Vec<f64>,
@@ -13,20 +20,90 @@ pub struct SystemSnapshot {
     pub used_memory: u64,
     pub total_disk: u64,
     pub used_disk: u64,
+    pub filesystems: Vec<FilesystemUsage>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FilesystemUsage {
+    pub mount_point: String,
+    pub total: u64,
+    pub used: u64,
+    /// Estimated time until the filesystem is full, if it is filling.
+    pub time_to_full: Option<Duration>,
 }
 
 pub struct SystemSource {
     sys: System,
     disks: Disks,
+    forecast_window: Duration,
+    disk_history: HashMap<String, VecDeque<(Instant, u64)>>,
 }
 
 impl SystemSource {
-    pub fn new() -> Self {
+    pub fn new(forecast_window: Duration) -> Self {
         let mut sys = System::new_all();
         sys.refresh_all();
         let disks = Disks::new_with_refreshed_list();
-        Self { sys, disks }
+        Self {
+            sys,
+            disks,
+            forecast_window,
+            disk_history: HashMap::new(),
+        }
+    }
+
+    fn record_usage(
+        &mut self,
+        mount_point: &str,
+        used: u64,
+        now: Instant,
+    ) -> &VecDeque<(Instant, u64)> {
+        let history = self
+            .disk_history
+            .entry(mount_point.to_string())
+            .or_default();
+        history.push_back((now, used));
+        while history
+            .front()
+            .is_some_and(|(t, _)| now.duration_since(*t) > self.forecast_window)
+        {
+            history.pop_front();
+        }
+        history
+    }
+}
+
+/// Fit a least-squares line through the usage history and extrapolate when
+/// it reaches `total`. Returns `None` unless usage is trending upwards fast
+/// enough to fill up within `MAX_FORECAST`.
+fn forecast_time_to_full(history: &VecDeque<(Instant, u64)>, total: u64) -> Option<Duration> {
+    let (first, _) = *history.front()?;
+    let (last, used) = *history.back()?;
+    if last.duration_since(first) < MIN_FORECAST_SPAN {
+        return None;
     }
+
+    let n = history.len() as f64;
+    let points = history
+        .iter()
+        .map(|(t, u)| (t.duration_since(first).as_secs_f64(), *u as f64));
+    let (sum_x, sum_y, sum_xy, sum_xx) = points.fold((0.0, 0.0, 0.0, 0.0), |acc, (x, y)| {
+        (acc.0 + x, acc.1 + y, acc.2 + x * y, acc.3 + x * x)
+    });
+    let denom = n * sum_xx - sum_x * sum_x;
+    if denom == 0.0 {
+        return None;
+    }
+    // bytes per second
+    let slope = (n * sum_xy - sum_x * sum_y) / denom;
+    if slope <= 0.0 {
+        return None;
+    }
+
+    let remaining = total.saturating_sub(used) as f64;
+    Duration::try_from_secs_f64(remaining / slope)
+        .ok()
+        .filter(|t| *t <= MAX_FORECAST)
 }
 
 #[async_trait]
@@ -48,12 +125,48 @@ impl DataSource for SystemSource {
             .map(|d| d.total_space() - d.available_space())
             .sum();
 
+        let now = Instant::now();
+        let mounts: Vec<(String, u64, u64)> = self
+            .disks
+            .iter()
+            .filter(|d| d.total_space() > 0)
+            .map(|d| {
+                (
+                    d.mount_point().to_string_lossy().to_string(),
+                    d.total_space(),
+                    d.total_space() - d.available_space(),
+                )
+            })
+            .collect();
+
+        let mut filesystems = Vec::with_capacity(mounts.len());
+        for (mount_point, total, used) in mounts {
+            // Bind mounts show up once per mount point; keep the first
+            if filesystems
+                .iter()
+                .any(|fs: &FilesystemUsage| fs.mount_point == mount_point)
+            {
+                continue;
+            }
+            let history = self.record_usage(&mount_point, used, now);
+            let time_to_full = forecast_time_to_full(history, total);
+            filesystems.push(FilesystemUsage {
+                mount_point,
+                total,
+                used,
+                time_to_full,
+            });
+        }
+        self.disk_history
+            .retain(|mount, _| filesystems.iter().any(|fs| &fs.mount_point == mount));
+
         Ok(DataSnapshot::System(SystemSnapshot {
             cpu_usages,
             total_memory,
             used_memory,
             total_disk,
             used_disk,
+            filesystems,
         }))
     }
 
@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+
+/// Human-readable byte count using binary (1024) units, e.g. `12.3 MB`.
+pub fn format_bytes(bytes: u64) -> String {
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+/// Coarse duration with the two most significant units, e.g. `3h 20m`.
+pub fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    let (days, hours, mins) = (secs / 86_400, secs / 3600 % 24, secs / 60 % 60);
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {mins}m")
+    } else if mins > 0 {
+        format!("{mins}m")
+    } else {
+        format!("{secs}s")
+    }
+}
+
+/// Single-unit age of something `secs` seconds old, e.g. `3 days ago`.
+pub fn format_age(secs: u64) -> String {
+    const STEPS: &[(u64, &str)] = &[
+        (365 * 86_400, "year"),
+        (30 * 86_400, "month"),
+        (7 * 86_400, "week"),
+        (86_400, "day"),
+        (3600, "hour"),
+        (60, "minute"),
+    ];
+    for &(unit, name) in STEPS {
+        let n = secs / unit;
+        if n > 0 {
+            let plural = if n == 1 { "" } else { "s" };
+            return format!("{n} {name}{plural} ago");
+        }
+    }
+    "just now".to_string()
+}
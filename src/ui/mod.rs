@@ -1,3 +1,6 @@
+pub mod format;
+pub mod input;
 pub mod layout;
 pub mod statusbar;
 pub mod tabs;
+pub mod textarea;
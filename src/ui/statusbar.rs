@@ -1,13 +1,18 @@
 use ratatui::{
     layout::Rect,
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::Paragraph,
     Frame,
 };
 
-pub fn draw_statusbar(f: &mut Frame, area: Rect) {
-    let status = Paragraph::new(Line::from(vec![
+pub fn draw_statusbar(
+    f: &mut Frame,
+    area: Rect,
+    warning: Option<&str>,
+    notification: Option<&str>,
+) {
+    let mut spans = vec![
         Span::styled(" q", Style::default().fg(Color::Yellow)),
         Span::raw(": Quit  "),
         Span::styled("Tab", Style::default().fg(Color::Yellow)),
@@ -16,7 +21,31 @@ pub fn draw_statusbar(f: &mut Frame, area: Rect) {
         Span::raw(": Prev  "),
         Span::styled("r", Style::default().fg(Color::Yellow)),
         Span::raw(": Refresh"),
-    ]));
+    ];
+
+    if let Some(warning) = warning {
+        spans.push(Span::raw("  "));
+        spans.push(Span::styled(
+            format!(" ⚠ {warning} "),
+            Style::default()
+                .fg(Color::White)
+                .bg(Color::Red)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+
+    if let Some(notification) = notification {
+        spans.push(Span::raw("  "));
+        spans.push(Span::styled(
+            format!(" ↓ {notification} "),
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+
+    let status = Paragraph::new(Line::from(spans));
 
     f.render_widget(status, area);
 }
@@ -0,0 +1,162 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::{Position, Rect},
+    text::Line,
+    widgets::{Block, Paragraph},
+    Frame,
+};
+
+/// Multi-line counterpart of `TextInput`, for commit messages.
+#[derive(Debug, Clone)]
+pub struct TextArea {
+    lines: Vec<String>,
+    /// Cursor row, and column in chars.
+    row: usize,
+    col: usize,
+}
+
+impl TextArea {
+    pub fn new(text: &str) -> Self {
+        let mut area = Self {
+            lines: Vec::new(),
+            row: 0,
+            col: 0,
+        };
+        area.set_text(text);
+        area
+    }
+
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    /// Replace the contents, leaving the cursor at the start.
+    pub fn set_text(&mut self, text: &str) {
+        self.lines = text.lines().map(str::to_string).collect();
+        if self.lines.is_empty() {
+            self.lines.push(String::new());
+        }
+        self.row = 0;
+        self.col = 0;
+    }
+
+    fn line_len(&self) -> usize {
+        self.lines[self.row].chars().count()
+    }
+
+    fn byte_index(&self) -> usize {
+        let line = &self.lines[self.row];
+        line.char_indices()
+            .nth(self.col)
+            .map(|(i, _)| i)
+            .unwrap_or(line.len())
+    }
+
+    /// Apply an editing key. Returns `false` for keys the area doesn't use
+    /// (Esc, Ctrl shortcuts, ...) so the owner can act on them.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            return false;
+        }
+        match key.code {
+            KeyCode::Char(c) => {
+                let i = self.byte_index();
+                self.lines[self.row].insert(i, c);
+                self.col += 1;
+            }
+            KeyCode::Enter => {
+                let i = self.byte_index();
+                let rest = self.lines[self.row].split_off(i);
+                self.row += 1;
+                self.lines.insert(self.row, rest);
+                self.col = 0;
+            }
+            KeyCode::Backspace => {
+                if self.col > 0 {
+                    self.col -= 1;
+                    let i = self.byte_index();
+                    self.lines[self.row].remove(i);
+                } else if self.row > 0 {
+                    let line = self.lines.remove(self.row);
+                    self.row -= 1;
+                    self.col = self.line_len();
+                    self.lines[self.row].push_str(&line);
+                }
+            }
+            KeyCode::Delete => {
+                if self.col < self.line_len() {
+                    let i = self.byte_index();
+                    self.lines[self.row].remove(i);
+                } else if self.row + 1 < self.lines.len() {
+                    let line = self.lines.remove(self.row + 1);
+                    self.lines[self.row].push_str(&line);
+                }
+            }
+            KeyCode::Left => {
+                if self.col > 0 {
+                    self.col -= 1;
+                } else if self.row > 0 {
+                    self.row -= 1;
+                    self.col = self.line_len();
+                }
+            }
+            KeyCode::Right => {
+                if self.col < self.line_len() {
+                    self.col += 1;
+                } else if self.row + 1 < self.lines.len() {
+                    self.row += 1;
+                    self.col = 0;
+                }
+            }
+            KeyCode::Up => {
+                self.row = self.row.saturating_sub(1);
+                self.col = self.col.min(self.line_len());
+            }
+            KeyCode::Down => {
+                self.row = (self.row + 1).min(self.lines.len() - 1);
+                self.col = self.col.min(self.line_len());
+            }
+            KeyCode::Home => self.col = 0,
+            KeyCode::End => self.col = self.line_len(),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Draw inside `block`, scrolled so the cursor stays visible. `styled`
+    /// lets the owner color individual lines (e.g. an overlong subject).
+    pub fn draw(
+        &self,
+        f: &mut Frame,
+        area: Rect,
+        block: Block,
+        styled: impl Fn(usize, &str) -> Line<'static>,
+    ) {
+        let inner = block.inner(area);
+        let height = inner.height.max(1) as usize;
+        let top = (self.row + 1).saturating_sub(height);
+        let width = inner.width.max(1) as usize;
+        let left = (self.col + 1).saturating_sub(width);
+
+        let lines: Vec<Line> = self
+            .lines
+            .iter()
+            .enumerate()
+            .skip(top)
+            .take(height)
+            .map(|(i, line)| styled(i, line))
+            .collect();
+        f.render_widget(
+            Paragraph::new(lines).block(block).scroll((0, left as u16)),
+            area,
+        );
+
+        let x = inner.x + (self.col - left) as u16;
+        let y = inner.y + (self.row - top) as u16;
+        f.set_cursor_position(Position::new(x, y));
+    }
+}